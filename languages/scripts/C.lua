@@ -6,32 +6,16 @@ local include_string   = ""
 local namespace_string = ""
 
 function ProcessArguments()
-	local includes  = {}
-	local ininclude = false
+	local includes = {}
 
-	for i = 1, #SMArguments do
-		local low = string.lower( SMArguments[ i ] )
-
-		if #SMArguments[ i ] > 0 and SMArguments[ i ][ 1 ] == "-" then
-			ininclude = false
-		end
-
-		if ininclude == false then
-			if low == "--i" or low == "--include" then
-				ininclude = true
-			end
-		else
-			table.insert( includes, "#include <" .. SMArguments[ i ] .. ">" )
-		end
-
-		if( low == "--ns" or low == "--namespace" ) and i < #SMArguments then
-			namespace_string = "namespace " .. SMArguments[ i ] .. "\n{"
-			i = i + 1
-		end
+	for i = 1, #SMOptions.includes do
+		table.insert( includes, "#include <" .. SMOptions.includes[ i ] .. ">" )
 	end
 
-	if #includes > 0 then
-		include_string = table.concat( includes, "\n" )
+	include_string = table.concat( includes, "\n" )
+
+	if #SMOptions.namespace > 0 then
+		namespace_string = "namespace " .. SMOptions.namespace .. "\n{"
 	end
 end
 function ReplaceMacro( macro )
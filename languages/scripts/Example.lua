@@ -4,24 +4,28 @@
 -- The following global variables will be defined by Srcmake before loading the language script:
 --   SMFileName  -- A string containing the name of the file without the directory and file extention.
 --   SMSafeName  -- A string containing the name of the file with all unsafe type name characters replaced. Use this for class/struct type names ect.
---   SMArguments -- An array of strings containing the extra arguments given when running Srcmake (everything after the name flag).
+--   SMArguments -- An array of strings containing the extra arguments given when running Srcmake (everything after the name flag except universal arguments).
+--   SMOptions   -- A table of the options declared in the language config, keyed by option name.
+--   SMVars      -- A table of the variables loaded from files given with `--vars`.
 
 -- Example local variable used to hold data from `ProcessArguments`.
-local y_arg = false
+local why_string = ""
 
 -- `ProcessArguments` is an optional function that is called only once, after the script is loaded.
--- This is where you can initialise your script and variables from `SMArguments`.
+-- This is where you can initialise your script and variables from `SMOptions`. The options have
+-- already been parsed and validated by Srcmake, so there is no need to process `SMArguments`.
 function ProcessArguments()
 
-	-- Iterate through the arguments.
-	for i = 1, #SMArguments do
-		-- Convert argument to lowercase to allow case-insensitive arguments.
-		local low = string.lower( SMArguments[ i ] )
+	-- Flags are booleans.
+	if SMOptions.why then
+		why_string = "Yes"
+	else
+		why_string = "No"
+	end
 
-		-- Check possible aliases for an argument.
-		if( low == "--y" or low == "--why" ) then
-			y_arg = true
-		end
+	-- Enums are always one of the declared choices.
+	if SMOptions.mode == "b" then
+		why_string = why_string .. " (b)"
 	end
 
 end
@@ -34,11 +38,7 @@ function ReplaceMacro( macro )
 
 	-- Replace our custom macro based on data we processed in ProcessArguments.
 	if macro == "$WHY$" then
-		if y_arg == true then
-			return "Yes"
-		else
-			return "No"
-		end
+		return why_string
 	end
 
 	-- Return the same macro back since we did not handle it.
@@ -6,4 +6,6 @@
 -- The following global variables will be defined by Srcmake before loading the language script.
 SMFileName  = ""  -- A string containing the name of the file without the directory and file extention.
 SMSafeName  = ""  -- A string containing the name of the file with all unsafe type name characters replaced. Use this for class/struct type names ect.
-SMArguments = { } -- An array of strings containing the extra arguments given when running Srcmake (everything after the name flag).
+SMArguments = { } -- An array of strings containing the extra arguments given when running Srcmake (everything after the name flag except universal arguments).
+SMOptions   = { } -- A table of the options declared in the language config, keyed by option name (flags are booleans, values and enums are strings, lists are arrays of strings).
+SMVars      = { } -- A table of the variables loaded from files given with `--vars`, with the same structure as the files.
@@ -14,158 +14,446 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-use std::env;
+use serde_json::{json, Map, Value};
+use std::{env, process::ExitCode};
 
 use crate::app::{version::Version, *};
-use crate::envpath::{add_to_path, remove_from_path};
-use crate::error::{box_error, SMResult};
+use crate::envpath::{add_to_path, path_status, remove_from_path};
+use crate::error::{error_chain, error_kind, SMError, SMResult};
 use crate::language::{language_index, load_languages};
 use crate::name::*;
 
 pub const VERSION: Version = Version::new(0, 2, 0, 0);
 
-fn process_args(args: Vec<String>) -> SMResult<Option<AppData>>
+fn print_language_help(
+	language: Option<String>,
+	filetype: Option<String>,
+	all: bool,
+) -> SMResult<()>
 {
-	let args = if args.len() >= 2
+	let lstr = match language
 	{
-		args[1..].to_vec()
-	}
-	else
-	{
-		print_usage();
-		return Ok(None);
+		Some(l) => l,
+		None if !all =>
+		{
+			print_help();
+			return Ok(());
+		}
+		None => String::new(),
 	};
 
-	let help = {
-		let a = args[0].to_lowercase();
-		a == "-h" || a == "-help"
-	};
+	let langs = load_languages(true)?;
+
+	if all
+	{
+		for lang in &langs
+		{
+			lang.print_help();
+			println!();
+		}
+
+		return Ok(());
+	}
 
-	let alen = args.len();
+	let index = language_index(&lstr, &langs);
 
-	if alen == 1
+	if index >= langs.len()
 	{
-		let a = args[0].to_lowercase();
+		return Err(Box::new(SMError::LanguageNotFound(lstr)));
+	}
 
-		if help
+	match filetype
+	{
+		Some(f) => langs[index].print_template_help(&f),
+		None =>
 		{
-			print_help();
+			langs[index].print_help();
+			Ok(())
+		}
+	}
+}
+fn print_list() -> SMResult<()>
+{
+	let langs = load_languages(false)?;
+
+	for lang in &langs
+	{
+		println!("{} ({}):", &lang.name, lang.aliases.join(", "));
+
+		for (filetype, exts) in lang.template_types()
+		{
+			println!("\t{filetype} [{}]", exts.join(", "));
 		}
-		else if a == "-v" || a == "-version"
+
+		println!();
+	}
+
+	Ok(())
+}
+fn run_path_command(command: PathCommand) -> SMResult<()>
+{
+	match command
+	{
+		PathCommand::Add =>
 		{
-			print_version();
+			println!("{}", add_to_path()?);
+			Ok(())
 		}
-		else if a == "-p" || a == "-path"
+		PathCommand::Remove =>
 		{
-			if let Err(e) = add_to_path()
-			{
-				return Err(box_error(&format!("{e}")));
-			}
+			println!("{}", remove_from_path()?);
+			Ok(())
 		}
-		else if a == "-rp" || a == "-remove-path"
+		PathCommand::Status =>
 		{
-			if let Err(e) = remove_from_path()
+			if path_status()?
 			{
-				return Err(box_error(&format!("{e}")));
+				println!("Srcmake is in the system PATH.");
 			}
+			else
+			{
+				println!("Srcmake is not in the system PATH.");
+			}
+
+			Ok(())
+		}
+	}
+}
+
+fn run_command(command: Command) -> SMResult<()>
+{
+	match command
+	{
+		Command::Usage =>
+		{
+			print_usage();
+			Ok(())
 		}
-		else
+		Command::Help {
+			language,
+			filetype,
+			all,
+		} => print_language_help(language, filetype, all),
+		Command::Version =>
 		{
-			return Err(box_error(
-				"Invalid argument(s). Run `>srcmake -h` for help.",
-			));
+			print_version();
+			Ok(())
 		}
+		Command::List => print_list(),
+		Command::Path(p) => run_path_command(p),
+		Command::Batch { plan, args } => run_batch(&plan, &args),
+		Command::Generate {
+			language,
+			filetype,
+			names,
+			args,
+			language_args,
+		} =>
+		{
+			let data = load_app_data(OutputFormat::Text)?;
+			let datas = generate_data(data, &language, filetype, &names, &args, language_args)?;
 
-		return Ok(None);
+			let report = generate_names(&datas, &mut Conflicts::new())?;
+			report.print();
+
+			match report.error()
+			{
+				Some(e) => Err(Box::new(e)),
+				None => Ok(()),
+			}
+		}
 	}
-	if alen == 2
+}
+// Runs the command, adding its output to the fields of the json object.
+fn run_json_command(command: Command, fields: &mut Map<String, Value>) -> SMResult<()>
+{
+	match command
 	{
-		if !help
+		Command::Usage
+		| Command::Help {
+			language: None,
+			all: false,
+			..
+		} => usage_json(fields),
+		Command::Help {
+			language,
+			filetype,
+			all,
+		} =>
 		{
-			return Err(box_error(
-				"Invalid argument(s). Run `>srcmake -h` for help.",
-			));
-		}
+			let langs = load_languages(true)?;
+
+			if all
+			{
+				fields.insert(
+					String::from("languages"),
+					langs.iter().map(language_json).collect(),
+				);
+
+				return Ok(());
+			}
 
-		let langs = load_languages(true)?;
-		let lstr = args[1].to_lowercase();
+			let lstr = language.unwrap_or_default();
+			let index = language_index(&lstr, &langs);
 
-		if lstr == "--all"
-		{
-			for lang in &langs
+			if index >= langs.len()
 			{
-				lang.print_help();
-				println!();
+				return Err(Box::new(SMError::LanguageNotFound(lstr)));
 			}
 
-			return Ok(None);
+			match filetype
+			{
+				Some(f) =>
+				{
+					fields.insert(String::from("template"), template_json(&langs[index], &f)?)
+				}
+				None => fields.insert(String::from("language"), language_json(&langs[index])),
+			};
+		}
+		Command::Version =>
+		{
+			fields.insert(String::from("version"), json!(VERSION.to_string()));
 		}
+		Command::List =>
+		{
+			let langs = load_languages(false)?;
 
-		let index = language_index(&lstr, &langs);
+			fields.insert(
+				String::from("languages"),
+				langs.iter().map(language_json).collect(),
+			);
+		}
+		Command::Path(p) =>
+		{
+			let message = match p
+			{
+				PathCommand::Add => Some(add_to_path()?),
+				PathCommand::Remove => Some(remove_from_path()?),
+				PathCommand::Status => None,
+			};
 
-		if index >= langs.len()
+			fields.insert(String::from("message"), json!(message));
+			fields.insert(String::from("in_path"), json!(path_status()?));
+		}
+		Command::Batch { plan, args } =>
 		{
-			return Err(box_error(&format!(
-				"Unable to get help for {}; no language found with that alias.",
-				&args[1]
-			)));
+			let result = batch_result(&plan, &args, OutputFormat::Json)?;
+			batch_json(&result, fields);
+
+			if let Some(e) = result.error()
+			{
+				return Err(Box::new(e));
+			}
 		}
+		Command::Generate {
+			language,
+			filetype,
+			names,
+			args,
+			language_args,
+		} =>
+		{
+			let data = load_app_data(OutputFormat::Json)?;
+			let datas = generate_data(data, &language, filetype, &names, &args, language_args)?;
 
-		langs[index].print_help();
-		return Ok(None);
-	}
+			let report = generate_names(&datas, &mut Conflicts::new())?;
+			report_json(&report, fields);
 
-	let mut data = if let Ok(d) = AppData::new()
-	{
-		d
+			if let Some(e) = report.error()
+			{
+				return Err(Box::new(e));
+			}
+		}
 	}
-	else
-	{
-		return Err(box_error(
-			"Failed loading languages. Does the language directory exist?",
-		));
-	};
 
-	if !data.set_language(&args[0])
+	Ok(())
+}
+
+fn load_app_data(format: OutputFormat) -> SMResult<AppData>
+{
+	// The error is kept as it is, so a language directory that cannot be read keeps its exit code.
+	let mut data = AppData::new()?;
+
+	data.format = format;
+	Ok(data)
+}
+// Sets up loaded app data for generating each name from the given language and filetype.
+fn generate_data(
+	mut data: AppData,
+	language: &str,
+	filetype: String,
+	names: &[String],
+	args: &[String],
+	language_args: Vec<String>,
+) -> SMResult<Vec<AppData>>
+{
+	if !data.set_language(language)
 	{
-		return Err(box_error(&format!(
-			"{} is not a valid language alias.",
-			&args[0]
-		)));
+		return Err(Box::new(SMError::LanguageNotFound(String::from(language))));
 	}
 
-	data.filetype = args[1].to_lowercase();
+	data.filetype = filetype;
+
+	// Arguments are set first as they decide which platform the name is validated for.
+	// Only the arguments srcmake does not consume itself are passed to the language script.
+	data.set_args(args)?;
+	data.args.extend(language_args);
+	data.set_options()?;
 
-	data.name = match is_valid_file_path(&args[2])
+	let mut datas: Vec<AppData> = Vec::new();
+
+	for name in names
 	{
-		true => args[2].clone(),
-		false =>
+		if let Err(e) = validate_file_path(name, data.path_platform())
 		{
-			return Err(box_error("Invalid name given."));
+			return Err(Box::new(SMError::InvalidName {
+				name: name.clone(),
+				source: e,
+			}));
 		}
+
+		let mut d = data.clone();
+		d.name = name.clone();
+		datas.push(d);
+	}
+
+	Ok(datas)
+}
+
+// Generates every entry of the plan with one load of the languages, reporting all failures.
+fn batch_result(plan: &str, args: &[String], format: OutputFormat) -> SMResult<BatchResult>
+{
+	let plan = BatchPlan::from_file(plan)?;
+	let base = load_app_data(format)?;
+	let mut result = BatchResult {
+		entries: plan.entries.len(),
+		..Default::default()
 	};
+	// One coordinator for the whole plan, so an answer for all files applies to every entry.
+	let mut conflicts = Conflicts::new();
+
+	for (i, entry) in plan.entries.iter().enumerate()
+	{
+		let (entry_args, language_args) = split_language_args(&entry.args);
+
+		// Entry arguments come last so they take precedence over the shared arguments.
+		let mut eargs = args.to_vec();
 
-	data.set_args(
-		if args.len() > 3
+		if let Some(out) = &entry.out
 		{
-			(&args[3..]).to_vec()
+			eargs.extend([String::from("--out"), out.clone()]);
 		}
-		else
+
+		eargs.extend_from_slice(entry_args);
+
+		let mut data = base.clone();
+		data.overwrite = plan.overwrite;
+
+		let generated = generate_data(
+			data,
+			&entry.language,
+			entry.filetype.clone(),
+			&expand_braces(&entry.name),
+			&eargs,
+			language_args,
+		)
+		.and_then(|d| generate_names(&d, &mut conflicts));
+
+		let failure = match generated
 		{
-			vec![]
-		},
+			Ok(r) =>
+			{
+				let failure = r
+					.first_failure()
+					.map(|kind| (kind, format!("{} file(s) failed to generate.", r.failed())));
+
+				result.report.append(r);
+				failure
+			}
+			Err(e) => Some((error_kind(e.as_ref()), error_chain(e.as_ref()))),
+		};
+
+		if let Some((kind, message)) = failure
+		{
+			result.failures.push(BatchFailure {
+				index: i + 1,
+				entry: entry.clone(),
+				kind,
+				message,
+			});
+		}
+	}
+
+	Ok(result)
+}
+fn run_batch(plan: &str, args: &[String]) -> SMResult<()>
+{
+	let result = batch_result(plan, args, OutputFormat::Text)?;
+
+	result.report.print();
+
+	println!(
+		"\nBatch summary: {} of {} entries generated successfully.",
+		result.entries - result.failures.len(),
+		result.entries
 	);
 
-	Ok(Some(data))
+	if !result.failures.is_empty()
+	{
+		println!("Failed entries:");
+
+		for f in &result.failures
+		{
+			println!("{f}");
+		}
+	}
+
+	match result.error()
+	{
+		Some(e) => Err(Box::new(e)),
+		None => Ok(()),
+	}
 }
 
-pub fn run_srcmake() -> SMResult<()>
+// Runs srcmake with the process arguments, printing any error in the requested format.
+pub fn run_srcmake() -> ExitCode
 {
-	let data = match process_args(env::args().collect())?
+	let mut args: Vec<String> = env::args().skip(1).collect();
+
+	let result = match take_format(&mut args)
 	{
-		Some(d) => d,
-		_ => return Ok(()),
+		Ok(OutputFormat::Json) =>
+		{
+			let mut fields = Map::new();
+			let (command, result) = match parse_command(&args)
+			{
+				Ok(c) => (Some(c.name()), run_json_command(c, &mut fields)),
+				Err(e) => (None, Err(e)),
+			};
+
+			print_json(command, fields, &result);
+			return exit_code(&result);
+		}
+		Ok(OutputFormat::Text) => parse_command(&args).and_then(run_command),
+		Err(e) => Err(e),
 	};
 
-	generate_files(&data)
+	// Errors go to stderr so they never end up in output printed with `--stdout`.
+	if let Err(e) = &result
+	{
+		eprintln!(
+			"Srcmake did not run successfully: {}",
+			error_chain(e.as_ref())
+		);
+	}
+
+	exit_code(&result)
+}
+fn exit_code(result: &SMResult<()>) -> ExitCode
+{
+	match result
+	{
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) => ExitCode::from(error_kind(e.as_ref()).exit_code()),
+	}
 }
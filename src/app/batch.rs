@@ -0,0 +1,223 @@
+// batch.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use std::{fmt::Display, fs};
+
+use crate::{app::GenerationReport, box_error, ErrorKind, SMError, SMResult};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchEntry
+{
+	pub language: String,
+	pub filetype: String,
+	pub name: String,
+	pub args: Vec<String>,
+	pub out: Option<String>,
+}
+impl Display for BatchEntry
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "{} {} {}", &self.language, &self.filetype, &self.name)
+	}
+}
+impl BatchEntry
+{
+	fn from_table(table: &toml::Table, index: usize) -> SMResult<Self>
+	{
+		let args = match table.get("args")
+		{
+			Some(toml::Value::Array(a)) =>
+			{
+				let mut args: Vec<String> = Vec::new();
+
+				for v in a
+				{
+					match v
+					{
+						toml::Value::String(s) => args.push(s.clone()),
+						_ =>
+						{
+							return Err(box_error(&format!(
+								"Entry {index} has 'args' that are not strings."
+							)))
+						}
+					}
+				}
+
+				args
+			}
+			Some(_) =>
+			{
+				return Err(box_error(&format!(
+					"Entry {index} has 'args' that is not an array."
+				)))
+			}
+			None => vec![],
+		};
+
+		Ok(Self {
+			language: entry_required(table, index, "language")?,
+			filetype: entry_required(table, index, "filetype")?.to_lowercase(),
+			name: entry_required(table, index, "name")?,
+			args,
+			out: entry_string(table, index, "out")?,
+		})
+	}
+}
+
+fn entry_required(table: &toml::Table, index: usize, key: &str) -> SMResult<String>
+{
+	match entry_string(table, index, key)?
+	{
+		Some(s) => Ok(s),
+		None => Err(box_error(&format!("Entry {index} is missing '{key}'."))),
+	}
+}
+fn entry_string(table: &toml::Table, index: usize, key: &str) -> SMResult<Option<String>>
+{
+	match table.get(key)
+	{
+		Some(toml::Value::String(s)) => Ok(Some(s.clone())),
+		Some(_) => Err(box_error(&format!(
+			"Entry {index} has a '{key}' that is not a string."
+		))),
+		None => Ok(None),
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchFailure
+{
+	// Entries are numbered from 1 in the order of the plan.
+	pub index: usize,
+	pub entry: BatchEntry,
+	pub kind: ErrorKind,
+	pub message: String,
+}
+impl Display for BatchFailure
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "\t{} ({}): {}", self.index, &self.entry, &self.message)
+	}
+}
+
+// The combined outcome of every entry of a plan.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BatchResult
+{
+	pub report: GenerationReport,
+	pub entries: usize,
+	pub failures: Vec<BatchFailure>,
+}
+impl BatchResult
+{
+	pub fn error(&self) -> Option<SMError>
+	{
+		match self.failures.first()
+		{
+			Some(f) => Some(SMError::Failed {
+				message: format!(
+					"{} of {} batch entries failed.",
+					self.failures.len(),
+					self.entries
+				),
+				kind: f.kind,
+			}),
+			None => self.report.error(),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchPlan
+{
+	// The conflict policy shared by all entries, `None` prompts the user.
+	pub overwrite: Option<bool>,
+	pub entries: Vec<BatchEntry>,
+}
+impl BatchPlan
+{
+	pub fn from_file(path: &str) -> SMResult<Self>
+	{
+		let text = match fs::read_to_string(path)
+		{
+			Ok(t) => t,
+			Err(e) => return Err(Box::new(SMError::io("Failed reading batch plan", path, e))),
+		};
+
+		match Self::from_toml(&text)
+		{
+			Ok(p) => Ok(p),
+			Err(e) => Err(box_error(&format!("Failed loading batch plan {path}: {e}"))),
+		}
+	}
+
+	pub fn from_toml(text: &str) -> SMResult<Self>
+	{
+		let table = match text.parse::<toml::Table>()
+		{
+			Ok(t) => t,
+			Err(e) => return Err(box_error(&format!("{e}"))),
+		};
+
+		let overwrite = match table.get("conflict")
+		{
+			Some(toml::Value::String(s)) => match s.to_lowercase().as_str()
+			{
+				"ask" => None,
+				"overwrite" => Some(true),
+				"skip" => Some(false),
+				_ =>
+				{
+					return Err(box_error(&format!(
+						"Unknown conflict policy '{s}'; expected ask, overwrite or skip."
+					)))
+				}
+			},
+			Some(_) => return Err(box_error("'conflict' is not a string.")),
+			None => None,
+		};
+
+		let mut entries: Vec<BatchEntry> = Vec::new();
+
+		match table.get("entry")
+		{
+			Some(toml::Value::Array(a)) =>
+			{
+				for (i, v) in a.iter().enumerate()
+				{
+					match v
+					{
+						toml::Value::Table(t) => entries.push(BatchEntry::from_table(t, i + 1)?),
+						_ => return Err(box_error(&format!("Entry {} is not a table.", i + 1))),
+					}
+				}
+			}
+			Some(_) => return Err(box_error("'entry' is not an array of tables.")),
+			None =>
+			{}
+		}
+
+		if entries.is_empty()
+		{
+			return Err(box_error("The plan has no [[entry]] tables."));
+		}
+
+		Ok(Self { overwrite, entries })
+	}
+}
@@ -0,0 +1,350 @@
+// command.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use crate::{name::expand_braces, usage_error, SMResult};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathCommand
+{
+	Add,
+	Remove,
+	Status,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command
+{
+	Usage,
+	Help
+	{
+		language: Option<String>,
+		filetype: Option<String>,
+		all: bool,
+	},
+	Version,
+	List,
+	Path(PathCommand),
+	Batch
+	{
+		plan: String,
+		// Universal arguments applied to every entry.
+		args: Vec<String>,
+	},
+	Generate
+	{
+		language: String,
+		filetype: String,
+		// Each name generates the template set, with brace groups already expanded.
+		names: Vec<String>,
+		// Arguments before `--`, these may contain universal arguments.
+		args: Vec<String>,
+		// Arguments after `--`, these are only passed to the language script.
+		language_args: Vec<String>,
+	},
+}
+impl Command
+{
+	// The name of the command in machine readable output.
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			Self::Usage => "usage",
+			Self::Help { .. } => "help",
+			Self::Version => "version",
+			Self::List => "list",
+			Self::Path(_) => "path",
+			Self::Batch { .. } => "batch",
+			Self::Generate { .. } => "generate",
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat
+{
+	#[default]
+	Text,
+	Json,
+}
+
+// Universal arguments are consumed by srcmake and never passed on to the language script, each
+// entry is the aliases of the argument and whether it takes a value.
+pub const UNIVERSAL_ARGS: [(&[&str], bool); 17] = [
+	(&["--au", "--author"], true),
+	(&["--o", "--overwrite"], false),
+	(&["--no", "--no-overwrite"], false),
+	(&["--out"], true),
+	(&["--allow-outside-root"], false),
+	(&["--portable-names"], false),
+	(&["--only"], true),
+	(&["--except"], true),
+	(&["--set"], true),
+	(&["--set-list"], true),
+	(&["--vars"], true),
+	(&["--dry-run"], false),
+	(&["--stdout"], false),
+	(&["--diff"], false),
+	(&["--check"], false),
+	(&["--conflict-default"], true),
+	// Taken from the arguments before the command is parsed, as it applies to every command.
+	(&["--format"], true),
+];
+
+const INVALID_ARGS: &str = "Invalid argument(s). Run `>srcmake help` for help.";
+
+pub fn is_help_flag(arg: &str) -> bool
+{
+	let a = arg.to_lowercase();
+	a == "-h" || a == "--help" || a == "-help"
+}
+
+// Returns the primary alias of the universal argument and whether it takes a value.
+pub fn universal_arg(arg: &str) -> Option<(&'static str, bool)>
+{
+	let a = arg.to_lowercase();
+
+	UNIVERSAL_ARGS
+		.iter()
+		.find(|(aliases, _)| aliases.contains(&a.as_str()))
+		.map(|(aliases, value)| (aliases[0], *value))
+}
+pub fn is_universal_arg(arg: &str) -> bool { universal_arg(arg).is_some() }
+
+fn parse_help(args: &[String]) -> SMResult<Command>
+{
+	if args.len() > 2
+	{
+		return Err(usage_error(INVALID_ARGS));
+	}
+	if args.first().is_some_and(|a| a.to_lowercase() == "--all")
+	{
+		return Ok(Command::Help {
+			language: None,
+			filetype: None,
+			all: true,
+		});
+	}
+
+	Ok(Command::Help {
+		language: args.first().cloned(),
+		filetype: args.get(1).map(|f| f.to_lowercase()),
+		all: false,
+	})
+}
+fn parse_path(args: &[String]) -> SMResult<Command>
+{
+	if args.len() != 1
+	{
+		return Err(usage_error(
+			"Expected one of `add`, `remove` or `status` after `path`.",
+		));
+	}
+
+	match args[0].to_lowercase().as_str()
+	{
+		"add" => Ok(Command::Path(PathCommand::Add)),
+		"remove" => Ok(Command::Path(PathCommand::Remove)),
+		"status" => Ok(Command::Path(PathCommand::Status)),
+		_ => Err(usage_error(&format!(
+			"Unknown path command `{}`; expected one of `add`, `remove` or `status`.",
+			&args[0]
+		))),
+	}
+}
+// Splits the arguments at `--`, arguments after it are only passed to the language script.
+pub fn split_language_args(args: &[String]) -> (&[String], Vec<String>)
+{
+	match args.iter().position(|a| a == "--")
+	{
+		Some(i) => (&args[..i], args[i + 1..].to_vec()),
+		None => (args, vec![]),
+	}
+}
+
+fn parse_batch(args: &[String]) -> SMResult<Command>
+{
+	match args.first()
+	{
+		Some(plan) if !plan.starts_with('-') => Ok(Command::Batch {
+			plan: plan.clone(),
+			args: args[1..].to_vec(),
+		}),
+		_ => Err(usage_error(
+			"Missing the batch plan file. Run `>srcmake help` for help.",
+		)),
+	}
+}
+fn parse_generate(args: &[String]) -> SMResult<Command>
+{
+	let (args, language_args) = split_language_args(args);
+
+	// `-h` in place of a positional argument asks for help with what has been given so far.
+	if let Some(i) = args.iter().take(3).position(|a| is_help_flag(a))
+	{
+		return parse_help(&args[..i.min(2)]);
+	}
+	if args.len() < 3
+	{
+		let missing = ["language", "filetype", "name"][args.len()];
+
+		return Err(usage_error(&format!(
+			"Missing the {missing} flag. Run `>srcmake help` for help."
+		)));
+	}
+
+	// Every argument before the first flag is a name.
+	let end = match args[3..].iter().position(|a| a.starts_with('-'))
+	{
+		Some(i) => i + 3,
+		None => args.len(),
+	};
+
+	let mut names: Vec<String> = Vec::new();
+
+	for n in args[2..end].iter().flat_map(|n| expand_braces(n))
+	{
+		if !names.contains(&n)
+		{
+			names.push(n);
+		}
+	}
+
+	Ok(Command::Generate {
+		language: args[0].clone(),
+		filetype: args[1].to_lowercase(),
+		names,
+		args: args[end..].to_vec(),
+		language_args,
+	})
+}
+
+// Removes `--format [text|json]` from the arguments before `--`, the last one given is used.
+pub fn take_format(args: &mut Vec<String>) -> SMResult<OutputFormat>
+{
+	let mut format = OutputFormat::Text;
+	let mut i = 0;
+
+	while i < args.len() && args[i] != "--"
+	{
+		if !args[i].eq_ignore_ascii_case("--format")
+		{
+			i += 1;
+			continue;
+		}
+
+		format = match args.get(i + 1).map(|f| f.to_lowercase()).as_deref()
+		{
+			Some("text") => OutputFormat::Text,
+			Some("json") => OutputFormat::Json,
+			Some(f) if !f.starts_with('-') =>
+			{
+				return Err(usage_error(&format!(
+					"Unknown output format '{}'; expected text or json.",
+					&args[i + 1]
+				)))
+			}
+			_ =>
+			{
+				return Err(usage_error(
+					"The --format argument expects a value. Run `>srcmake help` for help.",
+				))
+			}
+		};
+
+		args.drain(i..i + 2);
+	}
+
+	Ok(format)
+}
+
+pub fn parse_command(args: &[String]) -> SMResult<Command>
+{
+	let first = match args.first()
+	{
+		Some(a) => a.to_lowercase(),
+		None => return Ok(Command::Usage),
+	};
+	let rest = &args[1..];
+
+	match first.as_str()
+	{
+		"help" | "-h" | "--help" | "-help" => parse_help(rest),
+		"version" | "-v" | "--version" | "-version" if rest.is_empty() => Ok(Command::Version),
+		"list" | "--list" if rest.is_empty() => Ok(Command::List),
+		"path" => parse_path(rest),
+		"-p" | "-path" if rest.is_empty() => Ok(Command::Path(PathCommand::Add)),
+		"-rp" | "-remove-path" if rest.is_empty() => Ok(Command::Path(PathCommand::Remove)),
+		"batch" => parse_batch(rest),
+		"generate" | "new" => parse_generate(rest),
+		"version" | "-v" | "--version" | "-version" | "list" | "--list" | "-p" | "-path"
+		| "-rp" | "-remove-path" => Err(usage_error(INVALID_ARGS)),
+		_ if first.starts_with('-') => Err(usage_error(INVALID_ARGS)),
+		// The positional form `>srcmake [language] [filetype] [name]` is shorthand for generate.
+		_ => parse_generate(args),
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn strings(args: &[&str]) -> Vec<String> { args.iter().map(|a| String::from(*a)).collect() }
+
+	#[test]
+	fn universal_arg_finds_primary_alias()
+	{
+		assert_eq!(universal_arg("--AU"), Some(("--au", true)));
+		assert_eq!(universal_arg("--o"), Some(("--o", false)));
+		assert_eq!(universal_arg("--format"), Some(("--format", true)));
+		assert_eq!(universal_arg("--flag"), None);
+		assert_eq!(universal_arg("--"), None);
+	}
+
+	#[test]
+	fn language_args_pass_through()
+	{
+		let args = strings(&[
+			"rs", "struct", "Foo", "--au", "Jane", "--", "--au", "--format", "-x",
+		]);
+
+		match parse_command(&args).unwrap()
+		{
+			Command::Generate {
+				names,
+				args,
+				language_args,
+				..
+			} =>
+			{
+				assert_eq!(names, strings(&["Foo"]));
+				assert_eq!(args, strings(&["--au", "Jane"]));
+				assert_eq!(language_args, strings(&["--au", "--format", "-x"]));
+			}
+			c => panic!("expected a generate command, got {c:?}"),
+		}
+	}
+
+	#[test]
+	fn take_format_stops_at_language_args()
+	{
+		let mut args = strings(&["--format", "json", "--", "--format", "text"]);
+
+		assert_eq!(take_format(&mut args).unwrap(), OutputFormat::Json);
+		assert_eq!(args, strings(&["--", "--format", "text"]));
+	}
+}
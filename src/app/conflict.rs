@@ -0,0 +1,210 @@
+// conflict.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use std::{
+	io::{self, IsTerminal},
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	app::{AppData, OutputFormat},
+	name::validate_file_path,
+	usage_error, SMResult,
+};
+
+// The answer given for existing files when there is nobody to ask.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConflictPolicy
+{
+	#[default]
+	Skip,
+	Overwrite,
+	// Leaves existing files unresolved, which fails the run.
+	Fail,
+}
+impl ConflictPolicy
+{
+	pub fn from_name(name: &str) -> SMResult<Self>
+	{
+		match name.to_lowercase().as_str()
+		{
+			"skip" => Ok(Self::Skip),
+			"overwrite" => Ok(Self::Overwrite),
+			"fail" => Ok(Self::Fail),
+			_ => Err(usage_error(&format!(
+				"Unknown conflict policy '{name}'; expected overwrite, skip or fail."
+			))),
+		}
+	}
+}
+
+// What to do with a file that already exists.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Resolution
+{
+	Overwrite,
+	Skip,
+	// Write the file next to the existing one under another name.
+	Rename(PathBuf),
+	// Nobody could be asked or the policy is to fail, the file is left as it is and reported as a
+	// conflict.
+	Unresolved,
+}
+
+// Resolves every existing file from one thread, so prompts are asked one at a time and an answer
+// for all files applies to the ones after it, across every name that is generated.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Conflicts
+{
+	all: Option<Resolution>,
+}
+impl Conflicts
+{
+	pub fn new() -> Self { Self::default() }
+
+	// The answer for an existing file when the user is not asked, from the arguments, an earlier
+	// answer for all files, or the default policy when there is nobody to ask.
+	pub fn answer(&self, appdata: &AppData) -> Option<Resolution>
+	{
+		match (appdata.overwrite, &self.all)
+		{
+			(Some(true), _) => Some(Resolution::Overwrite),
+			(Some(false), _) => Some(Resolution::Skip),
+			(None, Some(all)) => Some(all.clone()),
+			// Json output is read by other programs, and a closed stdin would never be answered.
+			_ if appdata.format == OutputFormat::Json || !io::stdin().is_terminal() =>
+			{
+				match appdata.conflict_default
+				{
+					ConflictPolicy::Overwrite => Some(Resolution::Overwrite),
+					ConflictPolicy::Skip => Some(Resolution::Skip),
+					ConflictPolicy::Fail => Some(Resolution::Unresolved),
+				}
+			}
+			_ => None,
+		}
+	}
+
+	// Asks what to do with an existing file, given the diff to its regenerated content.
+	pub fn prompt(&mut self, appdata: &AppData, target: &Path, changes: &str) -> Resolution
+	{
+		let path = format!("{}", target.display());
+
+		if appdata.diff
+		{
+			print!("{changes}");
+		}
+
+		loop
+		{
+			println!(
+				"A file already exists at {path}. Would you like to overwrite it? [y]es, [n]o, \
+				 [a]ll, n[o]ne, [d]iff, [r]ename"
+			);
+
+			// Nothing more can be read once stdin is closed, so the rest are left unresolved too.
+			let answer = match read_answer()
+			{
+				Some(a) => a,
+				None =>
+				{
+					self.all = Some(Resolution::Unresolved);
+					return Resolution::Unresolved;
+				}
+			};
+
+			match answer.as_str()
+			{
+				"y" | "yes" => return Resolution::Overwrite,
+				"n" | "no" => return Resolution::Skip,
+				"a" | "all" | "yes-to-all" =>
+				{
+					self.all = Some(Resolution::Overwrite);
+					return Resolution::Overwrite;
+				}
+				"o" | "none" | "no-to-all" =>
+				{
+					self.all = Some(Resolution::Skip);
+					return Resolution::Skip;
+				}
+				"d" | "diff" if changes.is_empty() => println!("The file would not change."),
+				"d" | "diff" => print!("{changes}"),
+				"r" | "rename" =>
+				{
+					if let Some(renamed) = rename(appdata, target)
+					{
+						return Resolution::Rename(renamed);
+					}
+				}
+				_ => println!("Please answer y, n, a, o, d or r."),
+			}
+		}
+	}
+}
+
+fn read_answer() -> Option<String>
+{
+	let mut line = String::new();
+
+	match io::stdin().read_line(&mut line)
+	{
+		Ok(0) | Err(_) => None,
+		Ok(_) => Some(line.trim().to_lowercase()),
+	}
+}
+
+// Asks for a new file name in the directory of the target, an empty name goes back to the prompt.
+fn rename(appdata: &AppData, target: &Path) -> Option<PathBuf>
+{
+	loop
+	{
+		println!("Enter a new file name, or nothing to go back:");
+
+		let mut line = String::new();
+
+		if io::stdin().read_line(&mut line).is_err()
+		{
+			return None;
+		}
+
+		let name = line.trim();
+
+		if name.is_empty()
+		{
+			return None;
+		}
+		if name.contains(['/', '\\'])
+		{
+			println!("The new name must not contain a directory.");
+			continue;
+		}
+		if let Err(e) = validate_file_path(name, appdata.path_platform())
+		{
+			println!("{e}");
+			continue;
+		}
+
+		let renamed = target.with_file_name(name);
+
+		if renamed.exists()
+		{
+			println!("A file already exists at {}.", renamed.display());
+			continue;
+		}
+
+		return Some(renamed);
+	}
+}
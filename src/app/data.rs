@@ -14,14 +14,40 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-use std::env;
+use std::{env, path::PathBuf};
+#[cfg(test)]
+use std::{fs, process};
 
 use crate::{
-	language::{load_languages, Language},
+	app::{universal_arg, ConflictPolicy, OutputFormat},
+	language::{load_languages, parse_options, Language, OptionValue},
 	name::*,
+	paths::{get_extention, get_file_name},
+	project::Project,
+	usage_error,
+	vars::{load_vars, merge_vars, VarValue},
 	SMResult,
 };
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MacroValue
+{
+	Value(String),
+	List(Vec<String>),
+}
+impl MacroValue
+{
+	// Lists are written one item per line.
+	pub fn text(&self) -> String
+	{
+		match self
+		{
+			Self::Value(v) => v.clone(),
+			Self::List(l) => l.join("\n"),
+		}
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct AppData
 {
@@ -31,27 +57,160 @@ pub struct AppData
 	pub name: String,
 	pub overwrite: Option<bool>,
 	pub author: String,
+	pub only: Vec<String>,
+	pub except: Vec<String>,
+	pub portable_names: bool,
+	pub allow_outside_root: bool,
+	// Nothing is read from stdin when the output is machine readable.
+	pub format: OutputFormat,
+	// Runs the whole generation without writing any files.
+	pub dry_run: bool,
+	// Prints the rendered templates instead of writing them.
+	pub stdout: bool,
+	// Shows how existing files would change, and with check only compares them without writing.
+	pub diff: bool,
+	pub check: bool,
+	// The answer for existing files when stdin is not a terminal or the output is json.
+	pub conflict_default: ConflictPolicy,
 
 	pub directory: String,
+	pub project: Option<Project>,
 	pub args: Vec<String>,
+	pub options: Vec<(String, OptionValue)>,
+	// Macros defined on the command line, these take precedence over the language script.
+	pub macros: Vec<(String, MacroValue)>,
+	// Variables loaded from files, available to templates as macros and to the script as `SMVars`.
+	pub vars: Vec<(String, VarValue)>,
 }
 
 impl AppData
 {
 	pub fn new() -> SMResult<Self>
 	{
-		let langs = load_languages(false)?;
+		// Help is kept so unknown macros can be matched against the macros of the language.
+		let langs = load_languages(true)?;
+		let directory = format!("{}", env::current_dir().unwrap().display());
+
+		Ok(Self::with_languages(langs, directory))
+	}
+	// App data in a new directory of the temp dir, holding a Cargo.toml so tests never find a
+	// project in the directories of the host.
+	#[cfg(test)]
+	pub(crate) fn for_test(test: &str, languages: Vec<Language>) -> Self
+	{
+		let dir = env::temp_dir().join(format!("srcmake-{test}-{}", process::id()));
+
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(
+			dir.join("Cargo.toml"),
+			"[package]\nname = \"widgets\"\nversion = \"1.0.0\"\n",
+		)
+		.unwrap();
 
-		Ok(Self {
-			languages: langs,
+		Self::with_languages(languages, format!("{}", dir.display()))
+	}
+	fn with_languages(languages: Vec<Language>, directory: String) -> Self
+	{
+		Self {
+			languages,
 			language: Default::default(),
 			filetype: Default::default(),
 			name: Default::default(),
 			overwrite: None,
 			author: Default::default(),
+			only: Vec::new(),
+			except: Vec::new(),
+			portable_names: false,
+			allow_outside_root: false,
+			format: OutputFormat::Text,
+			dry_run: false,
+			stdout: false,
+			diff: false,
+			check: false,
+			conflict_default: ConflictPolicy::Skip,
 			args: Vec::new(),
-			directory: format!("{}", env::current_dir().unwrap().display()),
-		})
+			options: Vec::new(),
+			macros: Vec::new(),
+			vars: Vec::new(),
+			project: Project::find(&directory),
+			directory,
+		}
+	}
+
+	// Defines or overrides a macro from a `KEY=VALUE` argument, where a list is comma separated.
+	pub fn set_macro(&mut self, arg: &str, list: bool) -> SMResult<()>
+	{
+		let (key, value) = match arg.split_once('=')
+		{
+			Some(kv) => kv,
+			None =>
+			{
+				return Err(usage_error(&format!(
+					"Expected KEY=VALUE to set a macro, found '{arg}'."
+				)))
+			}
+		};
+
+		let key = key.trim().trim_matches('$').to_uppercase();
+
+		if !is_valid_name(&key)
+		{
+			return Err(usage_error(&format!("'{key}' is not a valid macro name.")));
+		}
+
+		let value = if list
+		{
+			MacroValue::List(
+				value
+					.split(',')
+					.map(|s| String::from(s.trim()))
+					.filter(|s| !s.is_empty())
+					.collect(),
+			)
+		}
+		else
+		{
+			MacroValue::Value(String::from(value))
+		};
+
+		let mac = format!("${key}$");
+
+		// The last definition of a macro wins.
+		self.macros.retain(|(m, _)| *m != mac);
+		self.macros.push((mac, value));
+
+		Ok(())
+	}
+
+	// Languages that declare their options have the script arguments parsed and validated here,
+	// otherwise the script is left to process `SMArguments` itself.
+	pub fn set_options(&mut self) -> SMResult<()>
+	{
+		let lang = match self.get_language()
+		{
+			Some(l) => l,
+			_ => return Ok(()),
+		};
+
+		if lang.options.is_empty()
+		{
+			return Ok(());
+		}
+
+		self.options = match parse_options(&lang.options, &self.args)
+		{
+			Ok(o) => o,
+			Err(e) =>
+			{
+				return Err(usage_error(&format!(
+					"{e} Run `>srcmake help {}` for help.",
+					lang.aliases.first().unwrap_or(&lang.name)
+				)))
+			}
+		};
+
+		Ok(())
 	}
 
 	pub fn get_language(&self) -> Option<&Language>
@@ -90,36 +249,198 @@ impl AppData
 		false
 	}
 
-	pub fn set_args(&mut self, args: Vec<String>)
+	// Consumes the universal arguments, only the remaining arguments are kept for the script.
+	pub fn set_args(&mut self, args: &[String]) -> SMResult<()>
 	{
-		self.args = args.to_vec();
+		self.args = vec![];
 
-		let alen = self.args.len();
+		let alen = args.len();
 		let mut i = 0;
 
 		while i < alen
 		{
-			let a = self.args[i].to_lowercase();
+			let (arg, takes_value) = match universal_arg(&args[i])
+			{
+				Some(u) => u,
+				None =>
+				{
+					self.args.push(args[i].clone());
+					i += 1;
+					continue;
+				}
+			};
 
-			if a == "--o" || a == "--overwrite"
+			let value = if takes_value
 			{
-				self.overwrite = Some(true);
+				match args.get(i + 1)
+				{
+					Some(v) if !v.starts_with('-') => v.clone(),
+					_ =>
+					{
+						return Err(usage_error(&format!(
+							"The {} argument expects a value. Run `>srcmake help` for help.",
+							&args[i]
+						)))
+					}
+				}
 			}
-			else if a == "--no" || a == "--no-overwrite"
+			else
 			{
-				self.overwrite = Some(false);
-			}
-			else if (a == "--au" || a == "--author") && i + 1 < alen
+				String::new()
+			};
+
+			match arg
 			{
-				self.author = self.args[i + 1].clone();
+				"--o" => self.overwrite = Some(true),
+				"--no" => self.overwrite = Some(false),
+				"--au" => self.author = value,
+				"--out" => self.set_directory(&value),
+				"--allow-outside-root" => self.allow_outside_root = true,
+				"--portable-names" => self.portable_names = true,
+				"--dry-run" => self.dry_run = true,
+				"--stdout" => self.stdout = true,
+				"--diff" => self.diff = true,
+				"--check" =>
+				{
+					self.check = true;
+					self.dry_run = true;
+				}
+				"--conflict-default" => self.conflict_default = ConflictPolicy::from_name(&value)?,
+				"--only" => self.only.append(&mut split_list(&value)),
+				"--except" => self.except.append(&mut split_list(&value)),
+				"--set" => self.set_macro(&value, false)?,
+				"--set-list" => self.set_macro(&value, true)?,
+				"--vars" => merge_vars(&mut self.vars, load_vars(&value)?),
+				_ =>
+				{}
 			}
 
-			i += 1;
+			i += if takes_value { 2 } else { 1 };
+		}
+
+		Ok(())
+	}
+
+	pub fn set_directory(&mut self, dir: &str)
+	{
+		// Relative directories are relative to the current working directory.
+		let path = match env::current_dir()
+		{
+			Ok(cwd) => cwd.join(dir),
+			Err(_) => PathBuf::from(dir),
+		};
+
+		self.directory = format!("{}", path.display());
+		self.project = Project::find(&self.directory);
+	}
+
+	pub fn path_platform(&self) -> PathPlatform
+	{
+		if self.portable_names
+		{
+			PathPlatform::Portable
+		}
+		else
+		{
+			PathPlatform::host()
 		}
 	}
 
+	pub fn file_name(&self) -> String
+	{
+		let name = get_file_name(&self.name, true);
+
+		match self.get_language()
+		{
+			Some(l) => l.naming.file_name(&name),
+			None => name,
+		}
+	}
+	pub fn type_name(&self) -> String
+	{
+		let name = get_file_name(&self.name, false);
+
+		match self.get_language()
+		{
+			Some(l) => l.naming.type_name(&name),
+			None => path_to_name(&name, '_'),
+		}
+	}
+
+	pub fn template_selected(&self, template: &str) -> bool
+	{
+		let file = get_file_name(template, true).to_lowercase();
+		let ext = get_extention(&file);
+		let matches = |f: &String| *f == ext || *f == file;
+
+		(self.only.is_empty() || self.only.iter().any(matches)) && !self.except.iter().any(matches)
+	}
+
 	pub fn valid(&self) -> bool
 	{
-		is_valid_file_path(&self.name) && self.language < self.languages.len()
+		validate_file_path(&self.name, self.path_platform()).is_ok()
+			&& self.language < self.languages.len()
+	}
+}
+
+fn split_list(list: &str) -> Vec<String>
+{
+	list.split(',')
+		.map(|s| s.trim().trim_start_matches('.').to_lowercase())
+		.filter(|s| !s.is_empty())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::SMError;
+
+	fn strings(args: &[&str]) -> Vec<String> { args.iter().map(|a| String::from(*a)).collect() }
+
+	#[test]
+	fn set_args_strips_universal_args()
+	{
+		let mut data = AppData::for_test("set-args", Vec::new());
+
+		data.set_args(&strings(&[
+			"--Au",
+			"Jane Doe",
+			"--flag",
+			"--o",
+			"--only",
+			".h,cpp",
+			"value",
+			"--DRY-RUN",
+		]))
+		.unwrap();
+
+		assert_eq!(data.args, strings(&["--flag", "value"]));
+		assert_eq!(data.author, "Jane Doe");
+		assert_eq!(data.overwrite, Some(true));
+		assert_eq!(data.only, strings(&["h", "cpp"]));
+		assert!(data.dry_run);
+	}
+
+	#[test]
+	fn set_args_requires_values()
+	{
+		for args in [
+			&["--au"][..],
+			&["--au", "--o"],
+			&["--au", "-x"],
+			&["--only", "--au", "Jane"],
+		]
+		{
+			let error = AppData::for_test("set-args-values", Vec::new())
+				.set_args(&strings(args))
+				.unwrap_err();
+
+			assert!(
+				matches!(error.downcast_ref::<SMError>(), Some(SMError::Usage(_))),
+				"expected a usage error for {args:?}, got {error:?}"
+			);
+		}
 	}
 }
@@ -15,18 +15,235 @@
 // If not, see <https://www.gnu.org/licenses/>.
 //
 use chrono::{Datelike, Month, Timelike, Utc};
-use mlua::Lua;
+use mlua::{Lua, Table, Value};
 use std::{
 	fs,
 	io::Write,
-	path::PathBuf,
+	path::{Component, Path, PathBuf},
 	thread::{self, JoinHandle},
 };
 
-use crate::{app::*, box_error, make_error, name::*, paths::*, SMError, SMResult};
+use crate::{
+	app::*,
+	box_error,
+	diagnostic::{scan_macros, suggest, Diagnostic, MacroSpan},
+	diff::unified_diff,
+	error_chain, error_kind,
+	language::OptionValue,
+	make_error,
+	name::*,
+	paths::*,
+	vars::{var_macros, VarValue},
+	ErrorKind, SMError, SMResult,
+};
+
+// The macros replaced for every language, sibling macros are handled separately.
+pub const UNIVERSAL_MACROS: [&str; 19] = [
+	"$FILE_NAME$",
+	"$FILE_EXT$",
+	"$REL_PATH$",
+	"$DIR$",
+	"$PROJECT_ROOT$",
+	"$NAME$",
+	"$AUTHOR$",
+	"$PROJECT_NAME$",
+	"$PROJECT_VERSION$",
+	"$CRATE_NAME$",
+	"$ROOT_NAMESPACE$",
+	"$DATETIME$",
+	"$DATE$",
+	"$TIME$",
+	"$YEAR$",
+	"$MONTH_NUM$",
+	"$MONTH$",
+	"$DAY$",
+	"$WEEKDAY$",
+];
+
+// Stands in for a `$$` escape while macros are replaced, it contains no `$` so it is never read
+// as part of a macro.
+const LITERAL_DOLLAR: &str = "\u{0}DOLLAR\u{0}";
+
+fn project_values(data: &AppData) -> (String, String, String, String)
+{
+	// Without a project manifest, fall back to the output directory name.
+	match &data.project
+	{
+		Some(p) => (
+			p.name.clone(),
+			p.version.clone(),
+			p.crate_name.clone(),
+			p.root_namespace.clone(),
+		),
+		None =>
+		{
+			let dirname = get_file_name(&data.directory, true);
+			let validname = as_valid_name(&dirname, '_');
+
+			(dirname, String::new(), validname.to_lowercase(), validname)
+		}
+	}
+}
+// The output directory of a template with the project macros it may contain replaced.
+fn output_dir(appdata: &AppData, tf: &str) -> Option<String>
+{
+	let dir = appdata.languages[appdata.language].output_dir(tf)?;
+	let (projname, _, cratename, rootns) = project_values(appdata);
+
+	Some(
+		dir.replace("$PROJECT_NAME$", &projname)
+			.replace("$CRATE_NAME$", &cratename)
+			.replace("$ROOT_NAMESPACE$", &rootns),
+	)
+}
+fn output_root(appdata: &AppData, tf: &str) -> PathBuf
+{
+	let mut root = PathBuf::from(&appdata.directory);
+
+	if let Some(dir) = output_dir(appdata, tf)
+	{
+		root.push(dir);
+	}
+
+	root
+}
+// The first directory of the output directory of a template, which is taken as its include root so
+// `include/$PROJECT_NAME$` gives paths that start with the project name.
+fn include_root(appdata: &AppData, tf: &str) -> PathBuf
+{
+	let mut root = PathBuf::from(&appdata.directory);
+
+	if let Some(dir) = output_dir(appdata, tf)
+	{
+		if let Some(first) = unify_separators(&dir)
+			.split('/')
+			.find(|d| !d.is_empty() && *d != ".")
+		{
+			root.push(first);
+		}
+	}
+
+	root
+}
+fn target_path(appdata: &AppData, tf: &str) -> PathBuf
+{
+	let dataname = unify_separators(&appdata.name);
+	let name = Path::new(&dataname);
+
+	// Absolute names are kept as they are on every platform, so the root check refuses them
+	// unless they are within the output directory.
+	let mut targetpath = match name.components().next()
+	{
+		Some(Component::RootDir | Component::Prefix(_)) =>
+		{
+			name.parent().map(PathBuf::from).unwrap_or_default()
+		}
+		_ =>
+		{
+			let mut path = output_root(appdata, tf);
+
+			// Names are relative to the output directory, push each of its directories in turn;
+			// the file name is added after naming conventions are applied.
+			if let Some((dirs, _)) = dataname.rsplit_once('/')
+			{
+				for d in dirs.split('/').filter(|d| !d.is_empty())
+				{
+					path.push(d);
+				}
+			}
+
+			path
+		}
+	};
+
+	let fname = appdata.file_name();
+	targetpath.push(fname + "." + get_extention(tf));
+	normalize_path(&targetpath)
+}
+fn project_root(data: &AppData) -> String
+{
+	match &data.project
+	{
+		Some(p) => p.root.clone(),
+		None => data.directory.clone(),
+	}
+}
+fn sibling_path(data: &AppData, templates: &[String], ext: &str) -> String
+{
+	// Prefer a template from the same set, otherwise assume the sibling already exists.
+	let tf = match templates
+		.iter()
+		.find(|t| get_extention(t).eq_ignore_ascii_case(ext))
+	{
+		Some(t) => t.clone(),
+		None => format!("{}.{ext}", &data.filetype),
+	};
+
+	relative_path(
+		&format!("{}", target_path(data, &tf).display()),
+		&format!("{}", include_root(data, &tf).display()),
+	)
+}
+
+fn replace_sibling_macros(data: &AppData, mut content: String, templates: &[String]) -> String
+{
+	const SIBLING: &str = "$SIBLING:";
+
+	let mut from = 0;
+
+	while let Some(i) = content[from..].find(SIBLING)
+	{
+		let begin = from + i;
+		let extbegin = begin + SIBLING.len();
+		let end = match content[extbegin..].find('$')
+		{
+			Some(e) => extbegin + e,
+			_ => break,
+		};
+
+		let sibling = sibling_path(data, templates, &content[extbegin..end]);
+
+		content.replace_range(begin..end + 1, &sibling);
+		from = begin + sibling.len();
+	}
 
-fn replace_universal_macros(data: &AppData, content: String, ext: &str) -> String
+	content
+}
+fn replace_set_macros(data: &AppData, mut content: String) -> String
 {
+	// Macros set with arguments take precedence over variables files.
+	for (mac, value) in &data.macros
+	{
+		content = content.replace(mac, &value.text());
+	}
+	for (mac, value) in var_macros(&data.vars)
+	{
+		content = content.replace(&mac, &value);
+	}
+
+	content
+}
+fn replace_universal_macros(
+	data: &AppData,
+	content: String,
+	tf: &str,
+	templates: &[String],
+) -> String
+{
+	let ext = get_extention(tf);
+	let target = format!("{}", target_path(data, tf).display());
+	let root = project_root(data);
+	let relpath = relative_path(&target, &root);
+	let reldir = match Path::new(&target).parent()
+	{
+		Some(d) => relative_path(&format!("{}", d.display()), &root),
+		None => String::new(),
+	};
+
+	// Macros set on the command line override universal macros and any left by the script.
+	let content = replace_set_macros(data, content);
+	let content = replace_sibling_macros(data, content, templates);
+
 	let now = Utc::now();
 	let hour = now.hour();
 	let year = now.year();
@@ -43,17 +260,23 @@ fn replace_universal_macros(data: &AppData, content: String, ext: &str) -> Strin
 		m
 	};
 
-	let safename = path_to_name(&data.name, '_');
+	let safename = data.type_name();
 	let author = &data.author;
 
+	let (projname, projver, cratename, rootns) = project_values(data);
+
 	content
-		.replace(
-			"$FILE_NAME$",
-			&format!("{}", get_file_name(&data.name, true)),
-		)
+		.replace("$FILE_NAME$", &data.file_name())
 		.replace("$FILE_EXT$", &ext)
+		.replace("$REL_PATH$", &relpath)
+		.replace("$DIR$", &reldir)
+		.replace("$PROJECT_ROOT$", &unify_separators(&root))
 		.replace("$NAME$", &safename)
 		.replace("$AUTHOR$", &author)
+		.replace("$PROJECT_NAME$", &projname)
+		.replace("$PROJECT_VERSION$", &projver)
+		.replace("$CRATE_NAME$", &cratename)
+		.replace("$ROOT_NAMESPACE$", &rootns)
 		.replace(
 			"$DATETIME$",
 			&format!(
@@ -77,21 +300,216 @@ fn replace_universal_macros(data: &AppData, content: String, ext: &str) -> Strin
 		.replace("$WEEKDAY$", &format!("{:?}", now.weekday()))
 }
 
-fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
+fn lua_options<'lua>(lua: &'lua Lua, options: &[(String, OptionValue)])
+	-> mlua::Result<Table<'lua>>
+{
+	let table = lua.create_table()?;
+
+	for (name, value) in options
+	{
+		match value
+		{
+			OptionValue::Flag(b) => table.set(name.as_str(), *b)?,
+			OptionValue::Value(v) => table.set(name.as_str(), v.as_str())?,
+			OptionValue::List(l) =>
+			{
+				table.set(name.as_str(), lua.create_sequence_from(l.clone())?)?
+			}
+		}
+	}
+
+	Ok(table)
+}
+
+fn lua_var<'lua>(lua: &'lua Lua, value: &VarValue) -> mlua::Result<Value<'lua>>
+{
+	Ok(match value
+	{
+		VarValue::String(s) => Value::String(lua.create_string(s)?),
+		VarValue::Bool(b) => Value::Boolean(*b),
+		VarValue::Integer(i) => Value::Integer(*i),
+		VarValue::Float(f) => Value::Number(*f),
+		VarValue::List(l) =>
+		{
+			let table = lua.create_table()?;
+
+			for v in l
+			{
+				table.push(lua_var(lua, v)?)?;
+			}
+
+			Value::Table(table)
+		}
+		VarValue::Table(t) => Value::Table(lua_vars(lua, t)?),
+	})
+}
+fn lua_vars<'lua>(lua: &'lua Lua, vars: &[(String, VarValue)]) -> mlua::Result<Table<'lua>>
+{
+	let table = lua.create_table()?;
+
+	for (key, value) in vars
+	{
+		table.set(key.as_str(), lua_var(lua, value)?)?;
+	}
+
+	Ok(table)
+}
+
+// Lua names the chunk in its messages, so the line is read from `name:line: ` and removed along
+// with the stack traceback.
+fn script_error(path: &str, name: &str, source: &str, e: mlua::Error) -> SMError
+{
+	let mut message = e.to_string();
+	let location = format!("{name}:");
+	let mut line = None;
+
+	if let Some(t) = message.find("\nstack traceback:")
+	{
+		message.truncate(t);
+	}
+
+	if let Some(i) = message.find(&location)
+	{
+		let begin = i + location.len();
+		let digits: String = message[begin..]
+			.chars()
+			.take_while(|c| c.is_ascii_digit())
+			.collect();
+
+		if let Ok(l) = digits.parse::<usize>()
+		{
+			let mut end = begin + digits.len();
+
+			if message[end..].starts_with(": ")
+			{
+				end += 2;
+			}
+
+			line = Some(l);
+			message.replace_range(i..end, "");
+		}
+	}
+
+	// mlua prefixes syntax errors, which lua messages may already start with.
+	if message.starts_with("syntax error: syntax error")
+	{
+		message.replace_range(.."syntax error: ".len(), "");
+	}
+
+	let diag = Diagnostic::new(path, message.trim());
+
+	SMError::ScriptError(Box::new(match line
+	{
+		Some(l) => diag.at_line(source, l),
+		None => diag,
+	}))
+}
+
+// Failures preparing or checking the language script, which have no line to point at.
+fn script_failure(path: &str, message: &str) -> SMError
+{
+	SMError::ScriptError(Box::new(Diagnostic::new(path, message)))
+}
+
+// Every macro the template could use, to tell unknown macros from typos of known ones.
+fn known_macros(data: &AppData) -> Vec<String>
+{
+	let mut known: Vec<String> = UNIVERSAL_MACROS.iter().map(|m| String::from(*m)).collect();
+
+	if let Some(help) = &data.languages[data.language].help
+	{
+		known.extend(help.macros.iter().map(|m| m.name.to_uppercase()));
+	}
+
+	known.extend(data.macros.iter().map(|(m, _)| m.clone()));
+	known.extend(var_macros(&data.vars).into_iter().map(|(m, _)| m));
+	known
+}
+// Macros of the template that are still in the content were not replaced by anything.
+fn unknown_macros(
+	tf: &str,
+	template: &str,
+	macros: &[MacroSpan],
+	content: &str,
+	known: &[String],
+) -> Vec<Diagnostic>
+{
+	let mut reported: Vec<&str> = Vec::new();
+	let mut diags: Vec<Diagnostic> = Vec::new();
+
+	for m in macros
+	{
+		if reported.contains(&m.text.as_str()) || !content.contains(&m.text)
+		{
+			continue;
+		}
+
+		let mut diag = Diagnostic::new(tf, &format!("unknown macro {}", &m.text)).at(
+			template,
+			m.offset,
+			m.text.len(),
+		);
+
+		let literal = format!("${}$", &m.text);
+
+		diag = match suggest(&m.text, known.iter().map(|k| k.as_str()))
+		{
+			Some(k) => diag.with_hint(&format!(
+				"did you mean {k}? Write {literal} for the text itself"
+			)),
+			None => diag.with_hint(&format!("write {literal} for the text itself")),
+		};
+
+		reported.push(&m.text);
+		diags.push(diag);
+	}
+
+	diags
+}
+
+// A rendered template waiting to be written, with the content of the file it would replace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenderedFile
+{
+	pub target: PathBuf,
+	pub content: String,
+	pub existing: Option<String>,
+}
+
+// The path of a generated file, which changes when it is renamed, its status, and the diff from
+// the file it replaces when --diff is given.
+type FileResult = Result<(String, FileStatus, Option<String>), SMError>;
+
+// The rendered templates of a name, in the order they are reported.
+type RenderedFiles = Vec<(String, Result<RenderedFile, SMError>)>;
+
+fn render_file(appdata: AppData, tf: &str, templates: &[String]) -> Result<RenderedFile, SMError>
 {
 	// Open template file and read in to string.
 	let mut content = match fs::read_to_string(&tf)
 	{
 		Ok(con) => con,
-		Err(e) =>
-		{
-			return Err(make_error(&format!(
-				"Unable to read template from file {tf} because: {e}"
-			)))
-		}
+		Err(e) => return Err(SMError::io("Unable to read template from file", tf, e)),
 	};
 
-	let fileext = get_extention(&tf);
+	// Malformed macros are reported before anything is replaced, so spans point into the template.
+	let template = content.clone();
+	let known = known_macros(&appdata);
+	let (macros, malformed, escapes) = scan_macros(tf, &template, &known);
+
+	if !malformed.is_empty()
+	{
+		return Err(SMError::TemplateError(malformed));
+	}
+
+	// Escaped dollars are hidden from every replacement and restored once the file is complete.
+	for e in escapes.iter().rev()
+	{
+		content.replace_range(*e..*e + 2, LITERAL_DOLLAR);
+	}
+
+	// Macros set on the command line take precedence over the language script.
+	content = replace_set_macros(&appdata, content);
 
 	if !appdata.languages[appdata.language]
 		.script_path
@@ -99,20 +517,18 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 		.is_empty()
 	{
 		// Get language script and ensure it exists.
-		let scriptdata = {
-			let mut scriptpath = scripts_dir();
-
-			scriptpath += &appdata.languages[appdata.language].script_path;
-
-			match fs::read_to_string(&scriptpath)
+		let scriptname = appdata.languages[appdata.language].script_path.clone();
+		let scriptpath = scripts_dir() + &scriptname;
+		let scriptdata = match fs::read_to_string(&scriptpath)
+		{
+			Ok(con) => con,
+			Err(e) =>
 			{
-				Ok(con) => con,
-				Err(e) =>
-				{
-					return Err(make_error(&format!(
-						"Unable to read language script {scriptpath} because: {e}"
-					)))
-				}
+				return Err(SMError::io(
+					"Unable to read language script",
+					&scriptpath,
+					e,
+				))
 			}
 		};
 
@@ -125,9 +541,10 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 				Ok(t) => t,
 				Err(e) =>
 				{
-					return Err(make_error(&format!(
-						"Failed creating lua table for SMArguments: {e}"
-					)))
+					return Err(script_failure(
+						&scriptpath,
+						&format!("Failed creating lua table for SMArguments: {e}"),
+					))
 				}
 			};
 
@@ -137,9 +554,10 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 			{
 				if la.set(ai, a.clone()).is_err()
 				{
-					return Err(make_error(&format!(
-						"Failed setting lua language script argument."
-					)));
+					return Err(script_failure(
+						&scriptpath,
+						"Failed setting lua language script argument.",
+					));
 				}
 
 				ai += 1;
@@ -148,6 +566,30 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 			la
 		};
 
+		let luaoptions = match lua_options(&lua, &appdata.options)
+		{
+			Ok(t) => t,
+			Err(e) =>
+			{
+				return Err(script_failure(
+					&scriptpath,
+					&format!("Failed creating lua table for SMOptions: {e}"),
+				))
+			}
+		};
+
+		let luavars = match lua_vars(&lua, &appdata.vars)
+		{
+			Ok(t) => t,
+			Err(e) =>
+			{
+				return Err(script_failure(
+					&scriptpath,
+					&format!("Failed creating lua table for SMVars: {e}"),
+				))
+			}
+		};
+
 		let globals = lua.globals();
 
 		// Define variables in lua that the scripts use.
@@ -155,31 +597,52 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 			.set("SMFileName", get_file_name(&tf, false))
 			.is_err()
 		{
-			return Err(make_error(&format!(
-				"Failed initialising lua variable SMFileName."
-			)));
+			return Err(script_failure(
+				&scriptpath,
+				"Failed initialising lua variable SMFileName.",
+			));
 		}
-		if globals
-			.set("SMSafeName", path_to_name(&appdata.name, '_'))
-			.is_err()
+		if globals.set("SMSafeName", appdata.type_name()).is_err()
 		{
-			return Err(make_error(&format!(
-				"Failed initialising lua variable SMSafeName."
-			)));
+			return Err(script_failure(
+				&scriptpath,
+				"Failed initialising lua variable SMSafeName.",
+			));
 		}
 		if globals.set("SMArguments", luaargs).is_err()
 		{
-			return Err(make_error(&format!(
-				"Failed setting lua language script arguments."
-			)));
+			return Err(script_failure(
+				&scriptpath,
+				"Failed setting lua language script arguments.",
+			));
+		}
+
+		if globals.set("SMOptions", luaoptions).is_err()
+		{
+			return Err(script_failure(
+				&scriptpath,
+				"Failed setting lua language script options.",
+			));
+		}
+
+		if globals.set("SMVars", luavars).is_err()
+		{
+			return Err(script_failure(
+				&scriptpath,
+				"Failed setting lua language script variables.",
+			));
 		}
 
 		// Load the script into lua.
-		match lua.load(&scriptdata).exec()
+		// Name the chunk after the script so lua errors report the line they happened on.
+		match lua
+			.load(&scriptdata)
+			.set_name(format!("@{scriptname}"))
+			.exec()
 		{
 			Ok(_) =>
 			{}
-			Err(e) => return Err(make_error(&format!("Failed parsing language script: {e}."))),
+			Err(e) => return Err(script_error(&scriptpath, &scriptname, &scriptdata, e)),
 		}
 		// Ensure script has required functionsand call ProcessArguments function from lua.
 		match lua.load("ReplaceMacro ~= nil").eval::<bool>()
@@ -188,34 +651,38 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 			{
 				if !f
 				{
-					return Err(make_error(&format!(
-						"Language script missing ReplaceMacro function."
-					)));
+					return Err(script_failure(
+						&scriptpath,
+						"Language script missing ReplaceMacro function.",
+					));
 				}
 			}
 			Err(e) =>
 			{
-				return Err(make_error(&format!(
-					"Failed ReplaceMacro check in language script: {e}"
-				)));
+				return Err(script_failure(
+					&scriptpath,
+					&format!("Failed ReplaceMacro check in language script: {e}"),
+				));
 			}
 		}
 		match lua.load("ProcessArguments ~= nil").eval::<bool>()
 		{
 			Ok(f) =>
 			{
-				if f && lua.load("ProcessArguments()").exec().is_err()
+				if f
 				{
-					return Err(make_error(&format!(
-						"Failed running ProcessArguments() in language script."
-					)));
+					if let Err(e) = lua.load("ProcessArguments()").exec()
+					{
+						return Err(script_error(&scriptpath, &scriptname, &scriptdata, e));
+					}
 				}
 			}
 			Err(e) =>
 			{
-				return Err(make_error(&format!(
-					"Failed ProcessArguments check in language script: {e}"
-				)))
+				return Err(script_failure(
+					&scriptpath,
+					&format!("Failed ProcessArguments check in language script: {e}"),
+				))
 			}
 		}
 
@@ -240,10 +707,14 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 					_ => break,
 				};
 
-				let macstr = String::from(&content[begin..end + 1].to_uppercase());
+				// The token is replaced as written, uppercasing may change its length (`ß` is `SS`).
+				let token = String::from(&content[begin..end + 1]);
+				let macstr = token.to_uppercase();
 
-				if !is_valid_name(&macstr[1..macstr.len() - 1])
+				// The closing `$` may open the next macro, so resume the search from it.
+				if !is_valid_name(&token[1..token.len() - 1])
 				{
+					mac = Some(end);
 					continue;
 				}
 
@@ -252,30 +723,28 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 					.eval::<String>()
 				{
 					Ok(f) => f,
-					Err(e) =>
-					{
-						return Err(make_error(&format!(
-							"Failed running ReplaceMacro in language script: {e}"
-						)))
-					}
+					Err(e) => return Err(script_error(&scriptpath, &scriptname, &scriptdata, e)),
 				};
 
 				if macstr != repl.to_uppercase()
 				{
-					content = content.replace(&macstr, &repl);
+					content = content.replace(&token, &repl);
 					replaced = true;
 				}
 
 				if repl.is_empty()
 				{
+					// Compare bytes, slicing a string next to a multi-byte character would panic.
+					let bytes = content.as_bytes();
+
 					if begin == 0
 					{
-						if &content[..1] == " "
+						if bytes.first() == Some(&b' ')
 						{
 							content.remove(begin);
 						}
 					}
-					else if &content[begin - 1..begin + 1] == "  "
+					else if bytes.get(begin - 1..begin + 1) == Some(b"  ")
 					{
 						content.remove(begin);
 					}
@@ -297,7 +766,7 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 	}
 
 	// Replace built-in macros (do this after the language to allow its script to override default behaviour).
-	content = replace_universal_macros(&appdata, content, fileext);
+	content = replace_universal_macros(&appdata, content, tf, templates);
 	// Cleanup whitespace.
 	let mut nlns = content.find("\n\n\n").is_some() || content.find("\r\n\r\n\r\n").is_some();
 
@@ -309,77 +778,142 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 		nlns = content.find("\n\n\n").is_some() || content.find("\r\n\r\n\r\n").is_some();
 	}
 
-	let mut targetpath = PathBuf::new();
-	targetpath.push(&appdata.directory);
+	let unknown = unknown_macros(tf, &template, &macros, &content, &known);
 
-	let dataname = appdata.name.replace("\\", "/");
-	let split: Vec<&str> = dataname.split("/").collect();
+	if !unknown.is_empty()
+	{
+		return Err(SMError::TemplateError(unknown));
+	}
 
-	if !split.len() > 1
+	content = content.replace(LITERAL_DOLLAR, "$");
+
+	let target = target_path(&appdata, tf);
+
+	// Printed files bypass any conflict with an existing file.
+	let existing = match fs::try_exists(&target)
 	{
-		for s in split
+		Ok(true) if !appdata.stdout => match fs::read_to_string(&target)
 		{
-			targetpath.push(s);
-		}
+			Ok(e) => Some(e),
+			Err(e) =>
+			{
+				return Err(SMError::io(
+					"Unable to read existing file",
+					&format!("{}", target.display()),
+					e,
+				))
+			}
+		},
+		_ => None,
+	};
 
-		targetpath.pop();
-	}
+	Ok(RenderedFile {
+		target,
+		content,
+		existing,
+	})
+}
 
-	_ = fs::create_dir_all(&targetpath);
+// Writes a rendered file, resolving a conflict with an existing file first. This is only called
+// from one thread so the user is asked about one file at a time.
+fn finish_file(appdata: &AppData, file: RenderedFile, conflicts: &mut Conflicts) -> FileResult
+{
+	let path = format!("{}", file.target.display());
 
-	let fname = get_file_name(&appdata.name, true);
-	targetpath.push(fname + "." + fileext);
+	if appdata.stdout
+	{
+		return Ok((path, FileStatus::Rendered(file.content), None));
+	}
 
-	let exists = match fs::try_exists(&targetpath)
+	let existing = match &file.existing
 	{
-		Ok(e) => e,
-		_ => false,
+		Some(e) => e,
+		None if appdata.dry_run => return Ok((path, FileStatus::Created, None)),
+		None =>
+		{
+			return write_file(&file.target, &file.content)
+				.map(|_| (path, FileStatus::Created, None))
+		}
 	};
 
-	if exists && appdata.overwrite.is_none()
+	let changes = unified_diff(
+		existing,
+		&file.content,
+		&path,
+		&format!("{path} (regenerated)"),
+	);
+	let diff = if appdata.diff && !changes.is_empty()
 	{
-		println!(
-			"A file already exists at {}. Would you like to overwrite it? (Y/N)",
-			targetpath.display()
-		);
-
-		let mut line = String::new();
+		Some(changes.clone())
+	}
+	else
+	{
+		None
+	};
 
-		if std::io::stdin().read_line(&mut line).is_err()
+	// A check only compares existing files with what they would be regenerated as.
+	if appdata.check
+	{
+		let status = if changes.is_empty()
 		{
-			return Ok(());
+			FileStatus::Unchanged
 		}
+		else
+		{
+			FileStatus::Changed
+		};
 
-		let answer = line.trim().to_lowercase();
+		return Ok((path, status, diff));
+	}
 
-		if answer != "y" && answer != "yes"
+	// A dry run never prompts, files it would ask about are reported as conflicts instead.
+	if appdata.dry_run
+	{
+		let status = match conflicts.answer(appdata)
 		{
-			return Ok(());
-		}
+			Some(Resolution::Overwrite) => FileStatus::Overwritten,
+			Some(Resolution::Unresolved) | None => FileStatus::Conflict,
+			Some(_) => FileStatus::Skipped,
+		};
+
+		return Ok((path, status, diff));
 	}
-	else if exists && appdata.overwrite.is_some()
+
+	let (resolution, diff) = match conflicts.answer(appdata)
 	{
-		let ov = appdata.overwrite.unwrap();
+		Some(r) => (r, diff),
+		// The diff was already shown with the prompt.
+		None => (conflicts.prompt(appdata, &file.target, &changes), None),
+	};
 
-		if !ov
+	match resolution
+	{
+		Resolution::Skip => Ok((path, FileStatus::Skipped, diff)),
+		Resolution::Unresolved => Ok((path, FileStatus::Conflict, diff)),
+		Resolution::Overwrite =>
 		{
-			println!(
-				"A file already exists at {}. It will not be overwritten.",
-				targetpath.display()
-			);
-
-			return Ok(());
+			write_file(&file.target, &file.content).map(|_| (path, FileStatus::Overwritten, diff))
 		}
-		else
+		Resolution::Rename(renamed) => write_file(&renamed, &file.content)
+			.map(|_| (format!("{}", renamed.display()), FileStatus::Created, None)),
+	}
+}
+
+fn write_file(targetpath: &Path, content: &str) -> Result<(), SMError>
+{
+	if let Some(dir) = targetpath.parent()
+	{
+		if let Err(e) = fs::create_dir_all(dir)
 		{
-			println!(
-				"A file already exists at {}. It will be overwritten.",
-				targetpath.display()
-			);
+			return Err(SMError::io(
+				"Unable to create directory",
+				&format!("{}", dir.display()),
+				e,
+			));
 		}
 	}
 
-	match fs::File::create(&targetpath)
+	match fs::File::create(targetpath)
 	{
 		Ok(mut file) => match file.write_all(content.as_bytes())
 		{
@@ -387,38 +921,37 @@ fn generate_file(appdata: AppData, tf: &str) -> Result<(), SMError>
 			{}
 			Err(e) =>
 			{
-				return Err(make_error(&format!(
-					"Unable to write file {}: {e}",
-					targetpath.display()
-				)))
+				return Err(SMError::io(
+					"Unable to write file",
+					&format!("{}", targetpath.display()),
+					e,
+				))
 			}
 		},
 		Err(e) =>
 		{
-			return Err(make_error(&format!(
-				"Unable to create file {}: {e}",
-				targetpath.display()
-			)))
+			return Err(SMError::io(
+				"Unable to create file",
+				&format!("{}", targetpath.display()),
+				e,
+			))
 		}
 	};
 
-	println!(
-		"Srcmake created the file: {} successfully.",
-		targetpath.display()
-	);
-
 	Ok(())
 }
 
-pub fn generate_files(appdata: &AppData) -> SMResult<()>
+// Renders the selected templates of a name concurrently, nothing is written or prompted for.
+fn render_files(appdata: &AppData) -> SMResult<RenderedFiles>
 {
 	if !appdata.valid()
 	{
-		if !is_valid_file_path(&appdata.name)
+		if let Err(e) = validate_file_path(&appdata.name, appdata.path_platform())
 		{
-			return Err(box_error(
-				"Unable to generate file(s): The given name is not a valid file name or path.",
-			));
+			return Err(Box::new(SMError::InvalidName {
+				name: appdata.name.clone(),
+				source: e,
+			}));
 		}
 
 		return Err(box_error("Unable to generate file(s): AppData is invalid."));
@@ -429,10 +962,10 @@ pub fn generate_files(appdata: &AppData) -> SMResult<()>
 
 		if templates.is_empty()
 		{
-			return Err(box_error(&format!(
-				"Unable to generate file(s): There are no templates for the language {}.",
-				&appdata.languages[appdata.language].name
-			)));
+			return Err(Box::new(SMError::TemplateNotFound {
+				language: appdata.languages[appdata.language].name.clone(),
+				filetype: appdata.filetype.clone(),
+			}));
 		}
 
 		let mut tfl: Vec<String> = Vec::new();
@@ -464,36 +997,284 @@ pub fn generate_files(appdata: &AppData) -> SMResult<()>
 
 		if tfl.is_empty()
 		{
-			return Err(box_error(&format!(
-				"The language {} does not have the template {}.",
-				&appdata.languages[appdata.language].name, &appdata.filetype
-			)));
+			return Err(Box::new(SMError::TemplateNotFound {
+				language: appdata.languages[appdata.language].name.clone(),
+				filetype: appdata.filetype.clone(),
+			}));
 		}
 
 		tfl
 	};
 
-	let mut ft: Vec<JoinHandle<Result<(), SMError>>> = Vec::new();
+	// The whole template set is kept so sibling macros resolve to files that were filtered out.
+	let selected: Vec<&String> = tfiles
+		.iter()
+		.filter(|t| appdata.template_selected(t))
+		.collect();
+
+	if selected.is_empty()
+	{
+		return Err(box_error(&format!(
+			"None of the {} templates for {} match the --only and --except filters.",
+			&appdata.languages[appdata.language].name, &appdata.filetype
+		)));
+	}
 
-	for tf in tfiles
+	// Naming conventions and output directories may produce paths the name flag alone did not.
+	for tf in &selected
+	{
+		let relpath = relative_path(
+			&format!("{}", target_path(appdata, tf).display()),
+			&appdata.directory,
+		);
+
+		if let Err(e) = validate_file_path(&relpath, appdata.path_platform())
+		{
+			return Err(Box::new(SMError::InvalidName {
+				name: relpath,
+				source: e,
+			}));
+		}
+
+		let target = target_path(appdata, tf);
+
+		// Printed files are never written, so they may resolve anywhere.
+		if !appdata.allow_outside_root
+			&& !appdata.stdout
+			&& !is_within(&target, Path::new(&appdata.directory))
+			&& !is_within(&target, Path::new(&project_root(appdata)))
+		{
+			return Err(Box::new(SMError::OutsideRoot {
+				path: format!("{}", resolve_path(&target).display()),
+				root: appdata.directory.clone(),
+			}));
+		}
+	}
+
+	let mut ft: Vec<(String, JoinHandle<Result<RenderedFile, SMError>>)> = Vec::new();
+
+	for tf in selected
 	{
 		let ad = appdata.clone();
+		let tf = tf.clone();
+		let templates = tfiles.clone();
 
-		ft.push(thread::spawn(move || return generate_file(ad, &tf)));
+		ft.push((
+			format!("{}", target_path(appdata, &tf).display()),
+			thread::spawn(move || render_file(ad, &tf, &templates)),
+		));
 	}
 
-	for t in ft
+	// Every file is waited on so one failure does not hide the outcome of the others.
+	Ok(ft
+		.into_iter()
+		.map(|(path, t)| match t.join()
+		{
+			Ok(r) => (path, r),
+			Err(_) => (path, Err(make_error("Failed generating file."))),
+		})
+		.collect())
+}
+
+fn finish_files(
+	appdata: &AppData,
+	files: RenderedFiles,
+	conflicts: &mut Conflicts,
+	report: &mut GenerationReport,
+)
+{
+	for (path, file) in files
 	{
-		match t.join()
+		match file.and_then(|f| finish_file(appdata, f, conflicts))
 		{
-			Ok(_) =>
-			{}
-			Err(_) =>
-			{
-				return Err(box_error("Failed generating file."));
-			}
-		};
+			Ok((path, status, diff)) => report.add_diff(&path, status, diff),
+			Err(e) => report.add(&path, FileStatus::Failed(e.kind(), error_chain(&e))),
+		}
 	}
+}
 
-	Ok(())
+// Renders the templates of a name, then resolves conflicts and writes them one at a time.
+pub fn generate_files(appdata: &AppData, conflicts: &mut Conflicts) -> SMResult<GenerationReport>
+{
+	let files = render_files(appdata)?;
+	let mut report = GenerationReport::new();
+	report.dry_run = appdata.dry_run;
+	report.check = appdata.check;
+
+	finish_files(appdata, files, conflicts, &mut report);
+
+	Ok(report)
+}
+
+// Errors are reduced to their kind and message to be sent back from the thread of a name.
+type NameResult = Result<RenderedFiles, (ErrorKind, String)>;
+
+// Renders the template set of each name concurrently, then writes them from this thread so
+// conflicts are resolved one at a time. A name that cannot be generated is reported as failed
+// along with the files of the other names.
+pub fn generate_names(datas: &[AppData], conflicts: &mut Conflicts) -> SMResult<GenerationReport>
+{
+	if datas.len() == 1
+	{
+		return generate_files(&datas[0], conflicts);
+	}
+
+	let mut tasks: Vec<(&AppData, JoinHandle<NameResult>)> = Vec::new();
+
+	for data in datas
+	{
+		let d = data.clone();
+
+		tasks.push((
+			data,
+			thread::spawn(move || {
+				render_files(&d).map_err(|e| (error_kind(e.as_ref()), error_chain(e.as_ref())))
+			}),
+		));
+	}
+
+	let mut report = GenerationReport::new();
+	report.dry_run = datas.iter().any(|d| d.dry_run);
+	report.check = datas.iter().any(|d| d.check);
+
+	for (data, task) in tasks
+	{
+		match task.join()
+		{
+			Ok(Ok(files)) => finish_files(data, files, conflicts, &mut report),
+			Ok(Err((kind, e))) => report.add(&data.name, FileStatus::Failed(kind, e)),
+			Err(_) => report.add(
+				&data.name,
+				FileStatus::Failed(ErrorKind::Other, String::from("Failed generating files.")),
+			),
+		}
+	}
+
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::language::{Language, Naming};
+
+	fn language(output_dirs: &[(&str, &str)], file_case: NameCase) -> Language
+	{
+		Language {
+			name: String::from("Test"),
+			template_dir: String::new(),
+			aliases: Vec::new(),
+			script_path: String::new(),
+			output_dirs: output_dirs
+				.iter()
+				.map(|(k, d)| (String::from(*k), String::from(*d)))
+				.collect(),
+			naming: Naming {
+				file_case,
+				..Default::default()
+			},
+			options: Vec::new(),
+			help: None,
+		}
+	}
+	fn data(test: &str, name: &str, language: Language) -> AppData
+	{
+		let mut data = AppData::for_test(test, vec![language]);
+		data.name = String::from(name);
+		data
+	}
+
+	#[test]
+	fn nested_names_resolve_under_directory()
+	{
+		for name in [
+			"ui/widgets/Button",
+			"ui\\widgets\\Button",
+			"./ui//widgets/Button",
+		]
+		{
+			let data = data("nested", name, language(&[], NameCase::Preserve));
+
+			assert_eq!(
+				target_path(&data, "Class.cs"),
+				Path::new(&data.directory).join("ui/widgets/Button.cs")
+			);
+		}
+	}
+
+	#[test]
+	fn absolute_names_stay_absolute()
+	{
+		let data = data(
+			"absolute",
+			"/etc/Foo",
+			language(&[("cs", "src")], NameCase::Snake),
+		);
+		let target = target_path(&data, "Class.cs");
+
+		assert_eq!(target, Path::new("/etc/foo.cs"));
+		assert!(!is_within(&target, Path::new(&data.directory)));
+	}
+
+	#[test]
+	fn nested_names_apply_file_case_last()
+	{
+		let data = data(
+			"file-case",
+			"Ui/MyWidgets/MyButton",
+			language(&[("rs", "src")], NameCase::Snake),
+		);
+
+		assert_eq!(
+			target_path(&data, "Struct.rs"),
+			Path::new(&data.directory).join("src/Ui/MyWidgets/my_button.rs")
+		);
+	}
+
+	#[test]
+	fn write_file_creates_nested_directories()
+	{
+		let data = data(
+			"write",
+			"ui/widgets/Button",
+			language(&[("hpp", "include")], NameCase::Preserve),
+		);
+		let target = target_path(&data, "Class.hpp");
+
+		assert_eq!(
+			target,
+			Path::new(&data.directory).join("include/ui/widgets/Button.hpp")
+		);
+
+		write_file(&target, "class Button;\n").unwrap();
+
+		assert_eq!(fs::read_to_string(&target).unwrap(), "class Button;\n");
+	}
+
+	#[test]
+	fn sibling_paths_replace_project_macros()
+	{
+		let mut data = data(
+			"sibling",
+			"ui/Widget",
+			language(
+				&[("hpp", "$PROJECT_NAME$/include"), ("cpp", "src")],
+				NameCase::Preserve,
+			),
+		);
+		data.filetype = String::from("class");
+
+		let templates = [String::from("Class.hpp"), String::from("Class.cpp")];
+
+		assert_eq!(
+			include_root(&data, "Class.hpp"),
+			Path::new(&data.directory).join("widgets")
+		);
+		assert_eq!(
+			sibling_path(&data, &templates, "hpp"),
+			"include/ui/Widget.hpp"
+		);
+		assert_eq!(sibling_path(&data, &templates, "cpp"), "ui/Widget.cpp");
+	}
 }
@@ -0,0 +1,213 @@
+// json.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use crate::{
+	app::{BatchResult, FileOutcome, FileStatus, GenerationReport, UNIVERSAL_ARGS, VERSION},
+	error_chain, error_kind,
+	language::{Language, LanguageOption, OptionKind},
+	ErrorKind, SMResult,
+};
+
+// The version of the output documented in the readme. It only changes when a field is removed or
+// changes meaning, new fields may be added without changing it.
+pub const JSON_SCHEMA: u32 = 1;
+
+fn kind_json(kind: ErrorKind, message: &str) -> Value
+{
+	json!({
+		"kind": kind.name(),
+		"code": kind.exit_code(),
+		"message": message,
+	})
+}
+pub fn error_json(e: &(dyn Error + 'static)) -> Value { kind_json(error_kind(e), &error_chain(e)) }
+
+fn file_json(file: &FileOutcome) -> Value
+{
+	let mut value = json!({
+		"path": &file.path,
+		"status": file.status.to_string(),
+	});
+
+	match &file.status
+	{
+		FileStatus::Rendered(content) => value["content"] = json!(content),
+		FileStatus::Failed(kind, reason) => value["error"] = kind_json(*kind, reason),
+		_ =>
+		{}
+	}
+
+	if let Some(diff) = &file.diff
+	{
+		value["diff"] = json!(diff);
+	}
+
+	value
+}
+pub fn report_json(report: &GenerationReport, fields: &mut Map<String, Value>)
+{
+	fields.insert(
+		String::from("files"),
+		report.files.iter().map(file_json).collect(),
+	);
+	fields.insert(
+		String::from("summary"),
+		json!({
+			"created": report.count(&FileStatus::Created),
+			"overwritten": report.count(&FileStatus::Overwritten),
+			"skipped": report.count(&FileStatus::Skipped),
+			"conflict": report.count(&FileStatus::Conflict),
+			"rendered": report.rendered().len(),
+			"changed": report.count(&FileStatus::Changed),
+			"unchanged": report.count(&FileStatus::Unchanged),
+			"failed": report.failed(),
+		}),
+	);
+	fields.insert(String::from("dry_run"), json!(report.dry_run));
+	fields.insert(String::from("check"), json!(report.check));
+}
+pub fn batch_json(result: &BatchResult, fields: &mut Map<String, Value>)
+{
+	report_json(&result.report, fields);
+
+	fields.insert(String::from("entries"), json!(result.entries));
+	fields.insert(
+		String::from("failed_entries"),
+		result
+			.failures
+			.iter()
+			.map(|f| {
+				json!({
+					"index": f.index,
+					"language": &f.entry.language,
+					"filetype": &f.entry.filetype,
+					"name": &f.entry.name,
+					"error": kind_json(f.kind, &f.message),
+				})
+			})
+			.collect(),
+	);
+}
+
+fn option_json(option: &LanguageOption) -> Value
+{
+	let (kind, choices) = match &option.kind
+	{
+		OptionKind::Enum(c) => (String::from("enum"), c.clone()),
+		k => (k.to_string(), vec![]),
+	};
+
+	json!({
+		"name": &option.name,
+		"kind": kind,
+		"choices": choices,
+		"aliases": option
+			.aliases
+			.iter()
+			.map(|a| json!({ "alias": &a.alias, "value": &a.value }))
+			.collect::<Vec<Value>>(),
+		"default": &option.default,
+		"description": &option.info,
+	})
+}
+pub fn language_json(lang: &Language) -> Value
+{
+	let (arguments, macros) = match &lang.help
+	{
+		Some(h) => (
+			h.arguments
+				.iter()
+				.map(|a| json!({ "aliases": &a.aliases, "description": &a.info }))
+				.collect(),
+			h.macros
+				.iter()
+				.map(|m| json!({ "name": &m.name, "description": &m.info }))
+				.collect(),
+		),
+		None => (vec![], vec![]),
+	};
+
+	json!({
+		"name": &lang.name,
+		"aliases": &lang.aliases,
+		"templates": lang
+			.template_types()
+			.iter()
+			.map(|(f, e)| json!({ "filetype": f, "extensions": e }))
+			.collect::<Vec<Value>>(),
+		"options": lang.options.iter().map(option_json).collect::<Vec<Value>>(),
+		"arguments": arguments,
+		"macros": macros,
+	})
+}
+pub fn template_json(lang: &Language, filetype: &str) -> SMResult<Value>
+{
+	let (templates, macros) = lang.template_help(filetype)?;
+
+	Ok(json!({
+		"language": &lang.name,
+		"filetype": filetype,
+		"templates": templates,
+		"macros": macros
+			.iter()
+			.map(|mac| {
+				let info = lang
+					.help
+					.as_ref()
+					.and_then(|h| h.macros.iter().find(|m| m.name == *mac))
+					.map(|m| &m.info);
+
+				json!({ "name": mac, "description": info })
+			})
+			.collect::<Vec<Value>>(),
+	}))
+}
+pub fn usage_json(fields: &mut Map<String, Value>)
+{
+	fields.insert(String::from("version"), json!(VERSION.to_string()));
+	fields.insert(
+		String::from("universal_arguments"),
+		UNIVERSAL_ARGS
+			.iter()
+			.map(|(aliases, value)| json!({ "aliases": aliases, "value": value }))
+			.collect(),
+	);
+}
+
+// Prints the whole output of a command as one object, nothing else is printed to stdout.
+pub fn print_json(command: Option<&str>, fields: Map<String, Value>, result: &SMResult<()>)
+{
+	let mut output = Map::new();
+
+	output.insert(String::from("schema"), json!(JSON_SCHEMA));
+	output.insert(String::from("command"), json!(command));
+	output.insert(String::from("ok"), json!(result.is_ok()));
+	output.extend(fields);
+
+	if let Err(e) = result
+	{
+		output.insert(String::from("error"), error_json(e.as_ref()));
+	}
+
+	match serde_json::to_string_pretty(&Value::Object(output))
+	{
+		Ok(s) => println!("{s}"),
+		Err(e) => eprintln!("Failed writing json output: {e}"),
+	}
+}
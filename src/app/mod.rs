@@ -15,13 +15,23 @@
 // If not, see <https://www.gnu.org/licenses/>.
 //
 mod app;
+mod batch;
+mod command;
+mod conflict;
 mod data;
 mod generator;
+mod json;
 mod print;
+mod report;
 mod version;
 
 pub use app::*;
-pub use data::AppData;
+pub use batch::*;
+pub use command::*;
+pub use conflict::*;
+pub use data::{AppData, MacroValue};
 pub use generator::*;
+pub use json::*;
 pub use print::*;
+pub use report::*;
 pub use version::*;
@@ -37,7 +37,9 @@ fn print_commands()
 		"The last required parameter is the name; this must contain only valid file path \
 		 characters. The name flag is used to name the generated file and must contain valid \
 		 characters for a file path; it is also often used by templates to name types, so any \
-		 characters that would be invalid in a type name will be replaced by underscores.\n"
+		 characters that would be invalid in a type name will be replaced by underscores. More \
+		 than one name may be given, and brace groups such as ui/{{Button,Label}} generate each \
+		 name.\n"
 	);
 
 	println!("[arguments] - Arguments");
@@ -58,31 +60,99 @@ fn print_arguments()
 		"\t--no|--no-overwrite - If this argument is given, Srcmake will skip generating \
 		 destination files without prompting."
 	);
+	println!(
+		"\t--out - Generates files in the following directory instead of the current working \
+		 directory."
+	);
+	println!(
+		"\t--allow-outside-root - Allows generating files outside of the output directory and \
+		 project root."
+	);
+	println!(
+		"\t--portable-names - Ensures generated file names and paths are valid on Windows, Linux \
+		 and macOS, instead of only the current platform."
+	);
+	println!(
+		"\t--only - Only generates templates whose extension or file name is in the following \
+		 comma separated list."
+	);
+	println!(
+		"\t--except - Skips generating templates whose extension or file name is in the following \
+		 comma separated list."
+	);
+	println!(
+		"\t--set - Defines or overrides a macro with the following KEY=VALUE argument, taking \
+		 precedence over the language script. May be given more than once."
+	);
+	println!(
+		"\t--set-list - Defines or overrides a macro with the following KEY=A,B,C argument, the \
+		 list is written one item per line."
+	);
+	println!(
+		"\t--vars - Loads macro values and lists from the following toml, json or cfg file, they \
+		 are also given to the language script in the SMVars table."
+	);
+	println!(
+		"\t--dry-run - Runs the language script and resolves every path and conflict, then prints \
+		 which files would be created or overwritten without writing anything."
+	);
+	println!(
+		"\t--stdout - Prints the rendered templates instead of writing files, each preceded by a \
+		 `==> [path] <==` line when more than one template is rendered."
+	);
+	println!(
+		"\t--conflict-default - The answer for existing files when stdin is not a terminal or the \
+		 output is json, either skip (the default), overwrite or fail to leave them unresolved \
+		 and exit with an error."
+	);
+	println!(
+		"\t--diff - Shows a unified diff between each existing file and its regenerated content, \
+		 and adds a d choice to the overwrite prompt that shows it."
+	);
+	println!(
+		"\t--check - Writes nothing and exits with an error when regenerating would change an \
+		 existing file, use with --diff to show the changes."
+	);
+	println!(
+		"\t--format - Prints the output of any command in the following format, either text or \
+		 json. With json nothing is prompted for and existing files are answered by --o, --no or \
+		 --conflict-default."
+	);
 }
 
 pub fn print_usage()
 {
 	println!("Srcmake usage:");
-	println!(">srcmake -h|-help ([language])");
+	println!(">srcmake help|-h|--help ([language] ([filetype])|--all)");
 	println!(
 		"Prints Srcmake usage help. If a language is specified, help for that language will be \
-		 printed. If `--all` is specified instead of a language, help will be printed for all \
-		 supported languages.\n"
+		 printed, and if a filetype is also specified, the templates and macros of that filetype \
+		 will be printed. If `--all` is specified instead of a language, help will be printed for \
+		 all supported languages.\n"
 	);
-	println!(">srcmake -v|-version");
+	println!(">srcmake version|-v|--version");
 	println!("Prints Srcmake version.\n");
-	println!(">srcmake -p|-path");
+	println!(">srcmake list");
+	println!("Prints the supported languages, their aliases and template filetypes.\n");
+	println!(">srcmake path add|remove|status");
+	println!(
+		"Adds or removes the Srcmake directory to or from the PATH system environment variable, \
+		 or prints whether it is in the PATH. Adding and removing requires admin privileges.\n"
+	);
+	println!(
+		">srcmake generate|new [language] [filetype] [name(s)] ([arguments]) (-- [language \
+		 arguments])"
+	);
 	println!(
-		"Adds the Srcmake directory to the PATH system environment variable. Requires admin \
-		 privileges.\n"
+		"Generates file(s) with the given language, filetype, name(s), and optional arguments. \
+		 Names may contain brace groups such as ui/{{Button,Label}}. Arguments after `--` are \
+		 only passed to the language script. `generate` may be omitted.\n"
 	);
-	println!(">srcmake -rp|-remove-path");
+	println!(">srcmake batch [plan] ([arguments])");
 	println!(
-		"Removes the Srcmake directory from the PATH system environment variable. Requires admin \
-		 privileges.\n"
+		"Generates every entry of the toml batch plan file, applying the given universal \
+		 arguments to all entries, then prints a summary of the entries that failed."
 	);
-	println!(">srcmake [language] [filetype] [name] ([arguments])");
-	println!("Generates file(s) with the given language, filetype, name, and optional arguments.");
 }
 pub fn print_help()
 {
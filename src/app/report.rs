@@ -0,0 +1,302 @@
+// report.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fmt::Display;
+
+use crate::{ErrorKind, SMError};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FileStatus
+{
+	Created,
+	Overwritten,
+	Skipped,
+	// The file exists and neither an argument nor an answer decided whether to overwrite it.
+	Conflict,
+	// The content of a file that was printed instead of written.
+	Rendered(String),
+	// A check found the file would be changed, or left as it is, by regenerating it.
+	Changed,
+	Unchanged,
+	Failed(ErrorKind, String),
+}
+impl Display for FileStatus
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Self::Created => write!(f, "created"),
+			Self::Overwritten => write!(f, "overwritten"),
+			Self::Skipped => write!(f, "skipped"),
+			Self::Conflict => write!(f, "conflict"),
+			Self::Rendered(_) => write!(f, "rendered"),
+			Self::Changed => write!(f, "changed"),
+			Self::Unchanged => write!(f, "unchanged"),
+			Self::Failed(..) => write!(f, "failed"),
+		}
+	}
+}
+impl FileStatus
+{
+	// What a dry run would do with the file.
+	pub fn planned(&self) -> &'static str
+	{
+		match self
+		{
+			Self::Created => "would create",
+			Self::Overwritten => "would overwrite",
+			Self::Skipped => "would skip",
+			Self::Conflict => "would ask to overwrite",
+			Self::Rendered(_) => "would render",
+			Self::Changed => "would change",
+			Self::Unchanged => "is up to date",
+			Self::Failed(..) => "would fail",
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileOutcome
+{
+	// The generated file, or the name when generation failed before a path was known.
+	pub path: String,
+	pub status: FileStatus,
+	// The unified diff from the existing file to the regenerated one, when --diff is given.
+	pub diff: Option<String>,
+}
+impl Display for FileOutcome
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match &self.status
+		{
+			// Diagnostics span several lines, which are indented to stay under the file.
+			FileStatus::Failed(_, reason) => write!(
+				f,
+				"\tfailed {}: {}",
+				&self.path,
+				reason.replace('\n', "\n\t\t")
+			),
+			s => write!(f, "\t{s} {}", &self.path),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GenerationReport
+{
+	pub files: Vec<FileOutcome>,
+	// Nothing was written, the statuses are what would have happened.
+	pub dry_run: bool,
+	// Existing files were compared with their regenerated content.
+	pub check: bool,
+}
+impl GenerationReport
+{
+	pub fn new() -> Self { Self::default() }
+
+	pub fn add(&mut self, path: &str, status: FileStatus) { self.add_diff(path, status, None); }
+	pub fn add_diff(&mut self, path: &str, status: FileStatus, diff: Option<String>)
+	{
+		self.files.push(FileOutcome {
+			path: String::from(path),
+			status,
+			diff,
+		});
+	}
+	pub fn append(&mut self, other: GenerationReport)
+	{
+		self.dry_run |= other.dry_run;
+		self.check |= other.check;
+		self.files.extend(other.files);
+	}
+
+	pub fn count(&self, status: &FileStatus) -> usize
+	{
+		self.files.iter().filter(|f| f.status == *status).count()
+	}
+	pub fn failed(&self) -> usize
+	{
+		self.files
+			.iter()
+			.filter(|f| matches!(f.status, FileStatus::Failed(..)))
+			.count()
+	}
+
+	pub fn first_failure(&self) -> Option<ErrorKind>
+	{
+		self.files.iter().find_map(|f| match f.status
+		{
+			FileStatus::Failed(kind, _) => Some(kind),
+			_ => None,
+		})
+	}
+
+	// Failures take precedence over files a check found out of date, then over files that already
+	// existed and were not overwritten, which a dry run only reports.
+	pub fn error(&self) -> Option<SMError>
+	{
+		if let Some(kind) = self.first_failure()
+		{
+			return Some(SMError::Failed {
+				message: format!("{} file(s) failed to generate.", self.failed()),
+				kind,
+			});
+		}
+
+		let changed: Vec<String> = self
+			.files
+			.iter()
+			.filter(|f| f.status == FileStatus::Changed)
+			.map(|f| f.path.clone())
+			.collect();
+
+		if !changed.is_empty()
+		{
+			return Some(SMError::OutOfDate(changed));
+		}
+
+		if self.dry_run
+		{
+			return None;
+		}
+
+		// Files the user chose to skip are a success, only those nobody decided about are not.
+		let conflicts: Vec<String> = self
+			.files
+			.iter()
+			.filter(|f| f.status == FileStatus::Conflict)
+			.map(|f| f.path.clone())
+			.collect();
+
+		if conflicts.is_empty()
+		{
+			None
+		}
+		else
+		{
+			Some(SMError::Conflict(conflicts))
+		}
+	}
+
+	pub fn rendered(&self) -> Vec<(&str, &str)>
+	{
+		self.files
+			.iter()
+			.filter_map(|f| match &f.status
+			{
+				FileStatus::Rendered(content) => Some((f.path.as_str(), content.as_str())),
+				_ => None,
+			})
+			.collect()
+	}
+
+	// Prints every outcome, with all failures listed together at the end.
+	pub fn print(&self)
+	{
+		let rendered = self.rendered();
+
+		// Only the content is printed to stdout so it can be piped, failures go to stderr.
+		if !rendered.is_empty()
+		{
+			for (path, content) in &rendered
+			{
+				if rendered.len() > 1
+				{
+					println!("==> {path} <==");
+				}
+
+				print!("{content}");
+
+				if !content.ends_with('\n')
+				{
+					println!();
+				}
+			}
+			for file in self
+				.files
+				.iter()
+				.filter(|f| matches!(f.status, FileStatus::Failed(..)))
+			{
+				eprintln!("{file}");
+			}
+
+			return;
+		}
+
+		for file in self
+			.files
+			.iter()
+			.filter(|f| !matches!(f.status, FileStatus::Failed(..)))
+		{
+			if self.dry_run
+			{
+				println!("\t{} {}", file.status.planned(), &file.path);
+			}
+			else
+			{
+				println!("{file}");
+			}
+
+			if let Some(diff) = &file.diff
+			{
+				print!("{diff}");
+			}
+		}
+		for file in self
+			.files
+			.iter()
+			.filter(|f| matches!(f.status, FileStatus::Failed(..)))
+		{
+			println!("{file}");
+		}
+
+		if self.check
+		{
+			println!(
+				"Check: {} file(s) out of date, {} up to date and {} missing.",
+				self.count(&FileStatus::Changed),
+				self.count(&FileStatus::Unchanged),
+				self.count(&FileStatus::Created)
+			);
+			return;
+		}
+		if self.dry_run
+		{
+			println!(
+				"Dry run, nothing was written. Srcmake would create {}, overwrite {}, skip {}, \
+				 ask about {} and fail {} file(s).",
+				self.count(&FileStatus::Created),
+				self.count(&FileStatus::Overwritten),
+				self.count(&FileStatus::Skipped),
+				self.count(&FileStatus::Conflict),
+				self.failed()
+			);
+			return;
+		}
+
+		println!(
+			"Srcmake created {}, overwrote {}, skipped {}, left {} unresolved and failed {} \
+			 file(s).",
+			self.count(&FileStatus::Created),
+			self.count(&FileStatus::Overwritten),
+			self.count(&FileStatus::Skipped),
+			self.count(&FileStatus::Conflict),
+			self.failed()
+		);
+	}
+}
@@ -0,0 +1,340 @@
+// diagnostic.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use std::fmt::Display;
+
+use crate::name::is_valid_name;
+
+// A problem located in a file, printed with the offending line and a caret under the span.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Diagnostic
+{
+	pub path: String,
+	pub message: String,
+	// Lines and columns start at 1 and are counted in characters, `None` when not located.
+	pub line: Option<usize>,
+	pub column: usize,
+	pub length: usize,
+	pub snippet: String,
+	pub hint: Option<String>,
+}
+impl Display for Diagnostic
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "{}", &self.message)?;
+
+		let line = match self.line
+		{
+			Some(l) => l.to_string(),
+			None =>
+			{
+				write!(f, "\n --> {}", &self.path)?;
+
+				if let Some(h) = &self.hint
+				{
+					write!(f, "\n  = hint: {h}")?;
+				}
+
+				return Ok(());
+			}
+		};
+		let pad = " ".repeat(line.len());
+
+		// Tabs are kept so the caret lines up however wide the terminal shows them.
+		let indent: String = self
+			.snippet
+			.chars()
+			.take(self.column - 1)
+			.map(|c| {
+				if c == '\t'
+				{
+					'\t'
+				}
+				else
+				{
+					' '
+				}
+			})
+			.collect();
+
+		write!(f, "\n{pad}--> {}:{line}:{}", &self.path, self.column)?;
+		write!(f, "\n{pad} |\n{line} | {}", &self.snippet)?;
+		write!(f, "\n{pad} | {indent}{}", "^".repeat(self.length.max(1)))?;
+
+		if let Some(h) = &self.hint
+		{
+			write!(f, "\n{pad} = hint: {h}")?;
+		}
+
+		Ok(())
+	}
+}
+impl Diagnostic
+{
+	pub fn new(path: &str, message: &str) -> Self
+	{
+		Self {
+			path: String::from(path),
+			message: String::from(message),
+			column: 1,
+			..Default::default()
+		}
+	}
+
+	// Points at `length` bytes of the source from `offset`, the span is cut at the end of the line.
+	pub fn at(mut self, source: &str, offset: usize, length: usize) -> Self
+	{
+		let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+		let end = source[offset..]
+			.find('\n')
+			.map_or(source.len(), |i| offset + i);
+
+		self.line = Some(source[..offset].matches('\n').count() + 1);
+		self.column = source[start..offset].chars().count() + 1;
+		self.length = source[offset..end.min(offset + length)].chars().count();
+		self.snippet = String::from(source[start..end].trim_end_matches('\r'));
+		self
+	}
+	// Points at the text of a line, for errors that only know the line they happened on.
+	pub fn at_line(self, source: &str, line: usize) -> Self
+	{
+		let mut offset = 0;
+
+		for (i, l) in source.split_inclusive('\n').enumerate()
+		{
+			if i + 1 == line
+			{
+				let text = l.trim_end();
+				let indent = text.len() - text.trim_start().len();
+
+				return self.at(source, offset + indent, text.len() - indent);
+			}
+
+			offset += l.len();
+		}
+
+		self
+	}
+	// Points at the key of a cfg document, searching every section when none is given, or at the
+	// section header when there is no key.
+	pub fn at_cfg_key(self, source: &str, section: Option<&str>, key: Option<&str>) -> Self
+	{
+		let mut offset = 0;
+		let mut header = None;
+		let mut in_section = section.is_none();
+
+		for l in source.split_inclusive('\n')
+		{
+			let text = l.trim();
+			let indent = l.len() - l.trim_start().len();
+
+			if text.starts_with('[') && text.ends_with(']')
+			{
+				let name = text[1..text.len() - 1].trim();
+
+				in_section = match section
+				{
+					Some(s) => s.eq_ignore_ascii_case(name),
+					None => true,
+				};
+
+				if in_section && header.is_none()
+				{
+					header = Some((offset + indent, text.len()));
+				}
+			}
+			else if in_section
+			{
+				if let (Some(key), Some((k, _))) = (key, text.split_once('='))
+				{
+					if k.trim().eq_ignore_ascii_case(key)
+					{
+						return self.at(source, offset + indent, k.trim_end().len());
+					}
+				}
+			}
+
+			offset += l.len();
+		}
+
+		match header
+		{
+			Some((o, len)) if section.is_some() => self.at(source, o, len),
+			_ => self,
+		}
+	}
+
+	pub fn with_hint(mut self, hint: &str) -> Self
+	{
+		self.hint = Some(String::from(hint));
+		self
+	}
+}
+
+// A macro found in a template, `$` followed by a name and a closing `$`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MacroSpan
+{
+	pub offset: usize,
+	pub text: String,
+}
+
+// Finds the macros of a template in order, reporting known macros that are never closed on their
+// line, along with the offsets of `$$` escapes for a literal `$`. Only names in uppercase are
+// macros, and an unclosed name is only reported when it is a known macro or close to one, so other
+// uses of `$` in a template are left alone.
+pub fn scan_macros(
+	path: &str,
+	source: &str,
+	known: &[String],
+) -> (Vec<MacroSpan>, Vec<Diagnostic>, Vec<usize>)
+{
+	let mut macros: Vec<MacroSpan> = Vec::new();
+	let mut malformed: Vec<Diagnostic> = Vec::new();
+	let mut escapes: Vec<usize> = Vec::new();
+	let bytes = source.as_bytes();
+	let mut i = 0;
+
+	while let Some(b) = source[i..].find('$').map(|b| i + b)
+	{
+		// The closing `$` of a macro is consumed with it, so `$A$$B$` is still two macros.
+		if bytes.get(b + 1) == Some(&b'$')
+		{
+			escapes.push(b);
+			i = b + 2;
+			continue;
+		}
+
+		let name_end = b
+			+ 1 + source[b + 1..]
+			.find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+			.unwrap_or(source.len() - b - 1);
+		let name = &source[b + 1..name_end];
+
+		if name.len() < 2 || !name.starts_with(|c: char| c.is_ascii_uppercase())
+		{
+			i = b + 1;
+			continue;
+		}
+
+		match bytes.get(name_end)
+		{
+			Some(b'$') =>
+			{
+				macros.push(MacroSpan {
+					offset: b,
+					text: String::from(&source[b..name_end + 1]),
+				});
+				i = name_end + 1;
+			}
+			// Sibling macros name an extension after the colon.
+			Some(b':') if name == "SIBLING" =>
+			{
+				let line_end = source[name_end..]
+					.find('\n')
+					.map_or(source.len(), |e| name_end + e);
+
+				match source[name_end..line_end].find('$')
+				{
+					Some(e) => i = name_end + e + 1,
+					None =>
+					{
+						malformed.push(
+							Diagnostic::new(path, &format!("unterminated macro ${name}:"))
+								.at(source, b, line_end - b)
+								.with_hint("macros are closed with `$`"),
+						);
+						i = line_end;
+					}
+				}
+			}
+			_ =>
+			{
+				let line_end = source[name_end..]
+					.find('\n')
+					.map_or(source.len(), |e| name_end + e);
+
+				let closed = format!("${name}$");
+				let similar = known.contains(&closed)
+					|| suggest(&closed, known.iter().map(|k| k.as_str())).is_some();
+
+				// A `$` later on the line may close a macro with a lowercase name.
+				if similar && !source[name_end..line_end].contains('$') && is_valid_name(name)
+				{
+					let hint = match suggest(&closed, known.iter().map(|k| k.as_str()))
+					{
+						Some(k) if !known.contains(&closed) => format!("did you mean {k}?"),
+						_ => format!("did you mean {closed}?"),
+					};
+
+					malformed.push(
+						Diagnostic::new(path, &format!("unterminated macro ${name}"))
+							.at(source, b, name.len() + 1)
+							.with_hint(&hint),
+					);
+				}
+
+				i = name_end;
+			}
+		}
+	}
+
+	(macros, malformed, escapes)
+}
+
+// The candidate closest to the name, ignoring case, within a third of its length.
+pub fn suggest<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+	I: IntoIterator<Item = &'a str>,
+{
+	let name = name.to_uppercase();
+	let limit = (name.chars().count() / 3).max(1);
+
+	candidates
+		.into_iter()
+		.map(|c| (edit_distance(&name, &c.to_uppercase()), c))
+		.filter(|(d, _)| *d > 0 && *d <= limit)
+		.min_by_key(|(d, _)| *d)
+		.map(|(_, c)| c)
+}
+fn edit_distance(a: &str, b: &str) -> usize
+{
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, ca) in a.chars().enumerate()
+	{
+		let mut prev = row[0];
+		row[0] = i + 1;
+
+		for (j, cb) in b.iter().enumerate()
+		{
+			let cur = row[j + 1];
+			row[j + 1] = if ca == *cb
+			{
+				prev
+			}
+			else
+			{
+				1 + prev.min(cur).min(row[j])
+			};
+			prev = cur;
+		}
+	}
+
+	row[b.len()]
+}
@@ -0,0 +1,170 @@
+// diff.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+
+// Lines of context around each change.
+const CONTEXT: usize = 3;
+
+// A line of the diff, with the index of the line in the old and new text before it is applied.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Edit
+{
+	tag: char,
+	old: usize,
+	new: usize,
+}
+
+// Returns the unified diff from the old text to the new, which is empty only if they are equal.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String
+{
+	if old == new
+	{
+		return String::new();
+	}
+
+	let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+
+	// Lines are compared with their endings, which would show every line of a file changed from
+	// CRLF to LF as removed and added again without any visible difference.
+	if old.lines().eq(new.lines()) && old.ends_with('\n') == new.ends_with('\n')
+	{
+		return diff + "Only the line endings differ.\n";
+	}
+
+	let a: Vec<&str> = old.split_inclusive('\n').collect();
+	let b: Vec<&str> = new.split_inclusive('\n').collect();
+	let edits = diff_lines(&a, &b);
+	let mut i = 0;
+
+	while let Some(first) = edits[i..].iter().position(|e| e.tag != ' ').map(|p| i + p)
+	{
+		// Changes closer than twice the context share a hunk.
+		let mut last = first;
+
+		while let Some(next) = edits[last + 1..]
+			.iter()
+			.position(|e| e.tag != ' ')
+			.map(|p| last + 1 + p)
+		{
+			if next - last > CONTEXT * 2
+			{
+				break;
+			}
+
+			last = next;
+		}
+
+		let begin = first.saturating_sub(CONTEXT);
+		let end = (last + CONTEXT + 1).min(edits.len());
+		let hunk = &edits[begin..end];
+
+		let old_count = hunk.iter().filter(|e| e.tag != '+').count();
+		let new_count = hunk.iter().filter(|e| e.tag != '-').count();
+		let old_start = hunk[0].old + usize::from(old_count > 0);
+		let new_start = hunk[0].new + usize::from(new_count > 0);
+
+		diff += &format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@\n");
+
+		for e in hunk
+		{
+			let line = match e.tag
+			{
+				'+' => b[e.new],
+				_ => a[e.old],
+			};
+
+			match line.strip_suffix('\n')
+			{
+				Some(l) => diff += &format!("{}{}\n", e.tag, l.strip_suffix('\r').unwrap_or(l)),
+				None => diff += &format!("{}{line}\n\\ No newline at end of file\n", e.tag),
+			}
+		}
+
+		i = end;
+	}
+
+	diff
+}
+
+// The edits that turn `a` into `b`, from the longest common subsequence of their lines.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Edit>
+{
+	// The common prefix and suffix are left out of the table to keep it small.
+	let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+	let suffix = a[prefix..]
+		.iter()
+		.rev()
+		.zip(b[prefix..].iter().rev())
+		.take_while(|(x, y)| x == y)
+		.count();
+	let (ma, mb) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+	let mut lcs = vec![vec![0u32; mb.len() + 1]; ma.len() + 1];
+
+	for i in (0..ma.len()).rev()
+	{
+		for j in (0..mb.len()).rev()
+		{
+			lcs[i][j] = if ma[i] == mb[j]
+			{
+				lcs[i + 1][j + 1] + 1
+			}
+			else
+			{
+				lcs[i + 1][j].max(lcs[i][j + 1])
+			};
+		}
+	}
+
+	let mut edits: Vec<Edit> = (0..prefix)
+		.map(|i| Edit {
+			tag: ' ',
+			old: i,
+			new: i,
+		})
+		.collect();
+	let (mut i, mut j) = (0, 0);
+
+	while i < ma.len() || j < mb.len()
+	{
+		let (old, new) = (prefix + i, prefix + j);
+
+		if i < ma.len() && j < mb.len() && ma[i] == mb[j]
+		{
+			edits.push(Edit { tag: ' ', old, new });
+			i += 1;
+			j += 1;
+		}
+		// Removed lines come before the lines that replace them.
+		else if i < ma.len() && (j == mb.len() || lcs[i + 1][j] >= lcs[i][j + 1])
+		{
+			edits.push(Edit { tag: '-', old, new });
+			i += 1;
+		}
+		else
+		{
+			edits.push(Edit { tag: '+', old, new });
+			j += 1;
+		}
+	}
+
+	edits.extend((0..suffix).map(|k| Edit {
+		tag: ' ',
+		old: a.len() - suffix + k,
+		new: b.len() - suffix + k,
+	}));
+	edits
+}
@@ -10,7 +10,7 @@ const FILENAME: &str = "/etc/profile.d/500_srcmake_to_path.sh";
 const FILENAME: &str = "/etc/paths.d/500_srcmake_to_path";
 
 #[cfg(target_os = "windows")]
-pub fn add_to_path() -> SMResult<()>
+pub fn add_to_path() -> SMResult<String>
 {
 	use winreg::{
 		enums::{HKEY_LOCAL_MACHINE, KEY_ALL_ACCESS},
@@ -47,14 +47,13 @@ pub fn add_to_path() -> SMResult<()>
 					"Failed creating the PATH system environment variable: {e}.",
 				)));
 			}
-			return Ok(());
+			return Ok(String::from("Srcmake was successfully added to the system PATH."));
 		}
 	};
 
 	if path.to_lowercase().find(&exedir.to_lowercase()).is_some()
 	{
-		println!("Srcmake is already in the system PATH.");
-		return Ok(());
+		return Ok(String::from("Srcmake is already in the system PATH."));
 	}
 
 	let path = (if !path.ends_with(';')
@@ -74,11 +73,12 @@ pub fn add_to_path() -> SMResult<()>
 		)));
 	}
 
-	println!("Srcmake was successfully added to the system PATH.");
-	Ok(())
+	Ok(String::from(
+		"Srcmake was successfully added to the system PATH.",
+	))
 }
 #[cfg(target_os = "windows")]
-pub fn remove_from_path() -> SMResult<()>
+pub fn remove_from_path() -> SMResult<String>
 {
 	use winreg::{
 		enums::{HKEY_LOCAL_MACHINE, KEY_ALL_ACCESS},
@@ -107,7 +107,7 @@ pub fn remove_from_path() -> SMResult<()>
 	let path: String = match reg_key.get_value("PATH")
 	{
 		Ok(p) => p,
-		Err(_) => return Ok(()),
+		Err(_) => return Ok(String::from("Srcmake is not in the system PATH.")),
 	};
 
 	let index = path.to_lowercase().find(&exedir.to_lowercase());
@@ -125,15 +125,52 @@ pub fn remove_from_path() -> SMResult<()>
 	}
 	else
 	{
-		return Ok(());
+		return Ok(String::from("Srcmake is not in the system PATH."));
 	}
 
-	println!("Srcmake was successfully removed from the system PATH.");
-	Ok(())
+	Ok(String::from(
+		"Srcmake was successfully removed from the system PATH.",
+	))
+}
+
+#[cfg(target_os = "windows")]
+pub fn path_status() -> SMResult<bool>
+{
+	use winreg::{
+		enums::{HKEY_LOCAL_MACHINE, KEY_READ},
+		RegKey,
+	};
+
+	let reg_key = match RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey_with_flags(
+		"SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment",
+		KEY_READ,
+	)
+	{
+		Ok(k) => k,
+		Err(e) =>
+		{
+			return Err(box_error(&format!(
+				"Unable to get read access to the Windows registry: {e}."
+			)));
+		}
+	};
+
+	let path: String = match reg_key.get_value("PATH")
+	{
+		Ok(p) => p,
+		Err(_) => return Ok(false),
+	};
+
+	let exedir = paths::executable_dir().replace("/", "\\");
+
+	Ok(path
+		.to_lowercase()
+		.find(exedir.trim_end_matches('\\').to_lowercase().as_str())
+		.is_some())
 }
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
-pub fn add_to_path() -> SMResult<()>
+pub fn add_to_path() -> SMResult<String>
 {
 	use std::fs;
 
@@ -153,11 +190,12 @@ pub fn add_to_path() -> SMResult<()>
 		)));
 	}
 
-	println!("Srcmake was successfully added to the system PATH in {FILENAME}.");
-	Ok(())
+	Ok(format!(
+		"Srcmake was successfully added to the system PATH in {FILENAME}."
+	))
 }
 #[cfg(any(target_os = "linux", target_os = "macos"))]
-pub fn remove_from_path() -> SMResult<()>
+pub fn remove_from_path() -> SMResult<String>
 {
 	use std::fs;
 
@@ -183,6 +221,28 @@ pub fn remove_from_path() -> SMResult<()>
 		}
 	}
 
-	println!("Srcmake was successfully removed from the system PATH in {FILENAME}.");
-	Ok(())
+	Ok(format!(
+		"Srcmake was successfully removed from the system PATH in {FILENAME}."
+	))
+}
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn path_status() -> SMResult<bool>
+{
+	use std::fs;
+
+	match fs::try_exists(&FILENAME)
+	{
+		Ok(false) => Ok(false),
+		Ok(true) => match fs::read_to_string(FILENAME)
+		{
+			Ok(content) => Ok(content.contains(paths::executable_dir().trim_end_matches('/'))),
+			Err(e) => Err(box_error(&format!(
+				"Cannot check if Srcmake is in the system PATH, unable to read file {FILENAME}: {e}."
+			))),
+		},
+		Err(e) => Err(box_error(&format!(
+			"Cannot check if Srcmake is in the system PATH, unable to get filesystem access to \
+			 {FILENAME}: {e}."
+		))),
+	}
 }
@@ -14,29 +14,240 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, io};
+
+use crate::{diagnostic::Diagnostic, name::InvalidPath};
+
+// Each kind of error exits the process with its own code, see the Exit Codes section in the readme.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind
+{
+	Other,
+	Usage,
+	LanguageNotFound,
+	TemplateNotFound,
+	InvalidName,
+	Script,
+	Io,
+	Conflict,
+	Template,
+	OutOfDate,
+	OutsideRoot,
+}
+impl ErrorKind
+{
+	pub fn exit_code(self) -> u8
+	{
+		match self
+		{
+			Self::Other => 1,
+			Self::Usage => 2,
+			Self::LanguageNotFound => 3,
+			Self::TemplateNotFound => 4,
+			Self::InvalidName => 5,
+			Self::Script => 6,
+			Self::Io => 7,
+			Self::Conflict => 8,
+			Self::Template => 9,
+			Self::OutOfDate => 10,
+			Self::OutsideRoot => 11,
+		}
+	}
+	// The name of the kind in machine readable output.
+	pub fn name(self) -> &'static str
+	{
+		match self
+		{
+			Self::Other => "other",
+			Self::Usage => "usage",
+			Self::LanguageNotFound => "language_not_found",
+			Self::TemplateNotFound => "template_not_found",
+			Self::InvalidName => "invalid_name",
+			Self::Script => "script",
+			Self::Io => "io",
+			Self::Conflict => "conflict",
+			Self::Template => "template",
+			Self::OutOfDate => "out_of_date",
+			Self::OutsideRoot => "outside_root",
+		}
+	}
+}
 
 #[derive(Debug)]
-pub struct SMError
+pub enum SMError
 {
-	message: String,
+	Message(String),
+	Usage(String),
+	LanguageNotFound(String),
+	TemplateNotFound
+	{
+		language: String,
+		filetype: String,
+	},
+	InvalidName
+	{
+		name: String,
+		source: InvalidPath,
+	},
+	ScriptError(Box<Diagnostic>),
+	// Unknown or malformed macros, every one found in the template is reported.
+	TemplateError(Vec<Diagnostic>),
+	// An invalid language cfg document, the section and key are located in the file when the
+	// language is loaded.
+	Config
+	{
+		section: Option<String>,
+		key: Option<String>,
+		message: String,
+	},
+	ConfigError(Box<Diagnostic>),
+	Io
+	{
+		message: String,
+		path: String,
+		source: io::Error,
+	},
+	// Files that already existed and were left as they were without being asked about.
+	Conflict(Vec<String>),
+	// Existing files that a check found would be changed by regenerating them.
+	OutOfDate(Vec<String>),
+	// A generated path that resolves outside of the output directory and project root.
+	OutsideRoot
+	{
+		path: String,
+		root: String,
+	},
+	// Generation that failed for several reasons, with the kind of the first failure.
+	Failed
+	{
+		message: String,
+		kind: ErrorKind,
+	},
 }
 impl SMError
 {
-	pub fn new(msg: &str) -> Self
+	pub fn new(msg: &str) -> Self { Self::Message(String::from(msg)) }
+
+	pub fn io(message: &str, path: &str, source: io::Error) -> Self
+	{
+		Self::Io {
+			message: String::from(message),
+			path: String::from(path),
+			source,
+		}
+	}
+
+	pub fn kind(&self) -> ErrorKind
 	{
-		Self {
-			message: String::from(msg),
+		match self
+		{
+			Self::Message(_) => ErrorKind::Other,
+			Self::Usage(_) => ErrorKind::Usage,
+			Self::LanguageNotFound(_) => ErrorKind::LanguageNotFound,
+			Self::TemplateNotFound { .. } => ErrorKind::TemplateNotFound,
+			Self::InvalidName { .. } => ErrorKind::InvalidName,
+			Self::ScriptError(_) => ErrorKind::Script,
+			Self::TemplateError(_) => ErrorKind::Template,
+			Self::Config { .. } | Self::ConfigError(_) => ErrorKind::Other,
+			Self::Io { .. } => ErrorKind::Io,
+			Self::Conflict(_) => ErrorKind::Conflict,
+			Self::OutOfDate(_) => ErrorKind::OutOfDate,
+			Self::OutsideRoot { .. } => ErrorKind::OutsideRoot,
+			Self::Failed { kind, .. } => *kind,
 		}
 	}
+	pub fn exit_code(&self) -> u8 { self.kind().exit_code() }
 }
 impl fmt::Display for SMError
 {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", &self.message) }
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Self::Message(m) | Self::Usage(m) | Self::Failed { message: m, .. } => write!(f, "{m}"),
+			Self::LanguageNotFound(l) => write!(f, "{l} is not a valid language alias."),
+			Self::TemplateNotFound { language, filetype } =>
+			{
+				write!(
+					f,
+					"The language {language} does not have the template {filetype}."
+				)
+			}
+			Self::InvalidName { name, .. } => write!(f, "Invalid name given '{name}'"),
+			Self::ScriptError(d) => write!(f, "Error in language script: {d}"),
+			Self::TemplateError(diags) =>
+			{
+				let diags: Vec<String> = diags.iter().map(|d| d.to_string()).collect();
+				write!(f, "{}", diags.join("\n"))
+			}
+			Self::Config { message, .. } => write!(f, "{message}"),
+			Self::ConfigError(d) => write!(f, "{d}"),
+			Self::Io { message, path, .. } => write!(f, "{message} {path}"),
+			Self::Conflict(paths) => write!(
+				f,
+				"{} file(s) already existed and were not overwritten.",
+				paths.len()
+			),
+			Self::OutOfDate(paths) => write!(
+				f,
+				"{} file(s) would be changed by regenerating them.",
+				paths.len()
+			),
+			Self::OutsideRoot { path, root } => write!(
+				f,
+				"Unable to generate file(s): {path} is outside of the output directory {root}. \
+				 Use --allow-outside-root to generate it anyway."
+			),
+		}
+	}
+}
+impl Error for SMError
+{
+	fn source(&self) -> Option<&(dyn Error + 'static)>
+	{
+		match self
+		{
+			Self::InvalidName { source, .. } => Some(source),
+			Self::Io { source, .. } => Some(source),
+			_ => None,
+		}
+	}
 }
-impl Error for SMError {}
 
 pub fn make_error(msg: &str) -> SMError { SMError::new(msg) }
 pub fn box_error(msg: &str) -> Box<SMError> { Box::new(make_error(msg)) }
+pub fn usage_error(msg: &str) -> Box<SMError> { Box::new(SMError::Usage(String::from(msg))) }
+pub fn config_error(section: Option<&str>, key: Option<&str>, msg: &str) -> Box<SMError>
+{
+	Box::new(SMError::Config {
+		section: section.map(String::from),
+		key: key.map(String::from),
+		message: String::from(msg),
+	})
+}
 
 pub type SMResult<T> = Result<T, Box<dyn Error>>;
+
+// Errors that did not come from srcmake are treated as `ErrorKind::Other`.
+pub fn error_kind(e: &(dyn Error + 'static)) -> ErrorKind
+{
+	match e.downcast_ref::<SMError>()
+	{
+		Some(sme) => sme.kind(),
+		None => ErrorKind::Other,
+	}
+}
+// The error message followed by the messages of its sources.
+pub fn error_chain(e: &(dyn Error + 'static)) -> String
+{
+	let mut message = e.to_string();
+	let mut source = e.source();
+
+	while let Some(s) = source
+	{
+		message = format!("{}: {s}", message.trim_end_matches('.'));
+		source = s.source();
+	}
+
+	message
+}
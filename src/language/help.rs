@@ -178,10 +178,9 @@ impl LanguageHelp
 		}
 	}
 
+	// The arguments header is printed by the language, along with its declared options.
 	pub fn print_help(&self)
 	{
-		println!("Language arguments:");
-
 		for arg in &self.arguments
 		{
 			println!("\t{}", arg);
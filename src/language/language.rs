@@ -22,10 +22,13 @@ use std::{
 };
 
 use crate::{
-	box_error,
-	language::LanguageHelp,
+	app::is_universal_arg,
+	config_error,
+	diagnostic::Diagnostic,
+	language::{LanguageHelp, LanguageOption, Naming},
 	make_error,
-	paths::{self, get_extention},
+	name::is_valid_name,
+	paths::{self, get_extention, get_file_name, unify_separators},
 	SMError, SMResult,
 };
 
@@ -36,6 +39,9 @@ pub struct Language
 	pub template_dir: String,
 	pub aliases: Vec<String>,
 	pub script_path: String,
+	pub output_dirs: Vec<(String, String)>,
+	pub naming: Naming,
+	pub options: Vec<LanguageOption>,
 	pub help: Option<LanguageHelp>,
 }
 impl Language
@@ -47,7 +53,9 @@ impl Language
 			Some(s) => s,
 			_ =>
 			{
-				return Err(box_error(
+				return Err(config_error(
+					Some("Language"),
+					None,
 					"Cannot load Language from document: No Language section.",
 				))
 			}
@@ -60,7 +68,9 @@ impl Language
 				KeyValue::String(st) => st.clone(),
 				_ =>
 				{
-					return Err(box_error(
+					return Err(config_error(
+						Some("Language"),
+						Some("name"),
 						"Cannot load Language from document: 'name' key has wrong value type in \
 						 Language section.",
 					))
@@ -68,7 +78,9 @@ impl Language
 			},
 			_ =>
 			{
-				return Err(box_error(
+				return Err(config_error(
+					Some("Language"),
+					Some("name"),
 					"Cannot load Language from document: No 'name' key in Language section.",
 				))
 			}
@@ -80,7 +92,9 @@ impl Language
 				KeyValue::String(st) => st.clone(),
 				_ =>
 				{
-					return Err(box_error(
+					return Err(config_error(
+						Some("Language"),
+						Some("template_dir"),
 						"Cannot load Language from document: 'template_dir' key has wrong value \
 						 type in Language section.",
 					))
@@ -88,7 +102,9 @@ impl Language
 			},
 			_ =>
 			{
-				return Err(box_error(
+				return Err(config_error(
+					Some("Language"),
+					Some("template_dir"),
 					"Cannot load Language from document: No 'template_dir' key in Language \
 					 section.",
 				))
@@ -101,15 +117,19 @@ impl Language
 				KeyValue::StringArray(a) => a.clone(),
 				_ =>
 				{
-					return Err(box_error(
-						"Cannot load Language from document: 'template_dir' key has wrong value \
-						 type in Language section.",
+					return Err(config_error(
+						Some("Language"),
+						Some("aliases"),
+						"Cannot load Language from document: 'aliases' key has wrong value type \
+						 in Language section.",
 					))
 				}
 			},
 			_ =>
 			{
-				return Err(box_error(
+				return Err(config_error(
+					Some("Language"),
+					Some("aliases"),
 					"Cannot load Language from document: No 'aliases' key in Language section.",
 				))
 			}
@@ -121,7 +141,9 @@ impl Language
 				KeyValue::String(st) => st.clone(),
 				_ =>
 				{
-					return Err(box_error(
+					return Err(config_error(
+						Some("Language"),
+						Some("script"),
 						"Cannot load Language from document: 'script' key has wrong value type in \
 						 Language section.",
 					))
@@ -129,13 +151,50 @@ impl Language
 			},
 			_ =>
 			{
-				return Err(box_error(
+				return Err(config_error(
+					Some("Language"),
+					Some("script"),
 					"Cannot load Language from document: No 'script' key in Language section.",
 				))
 			}
 		};
 
-		let help = if read_help && doc.contains("Help")
+		let output_dirs = match section.get("output_dirs")
+		{
+			Some(s) => match &s.value
+			{
+				KeyValue::StringArray(a) =>
+				{
+					if a.len() % 2 == 1
+					{
+						return Err(config_error(
+							Some("Language"),
+							Some("output_dirs"),
+							"Cannot load Language from document: 'output_dirs' array contains an \
+							 odd number of strings.",
+						));
+					}
+
+					a.chunks(2)
+						.map(|p| (p[0].trim().to_lowercase(), unify_separators(p[1].trim())))
+						.collect()
+				}
+				_ =>
+				{
+					return Err(config_error(
+						Some("Language"),
+						Some("output_dirs"),
+						"Cannot load Language from document: 'output_dirs' key has wrong value \
+						 type in Language section.",
+					))
+				}
+			},
+			_ => vec![],
+		};
+
+		let naming = Naming::from_section(section)?;
+
+		let help = if doc.contains("Help")
 		{
 			match LanguageHelp::from_section(doc.get("Help").unwrap())
 			{
@@ -148,11 +207,56 @@ impl Language
 			None
 		};
 
+		let options = match doc.get("Options")
+		{
+			Some(s) => match LanguageOption::from_section(s)
+			{
+				Ok(o) => o,
+				Err(e) =>
+				{
+					return Err(config_error(
+						Some("Options"),
+						Some("arguments"),
+						&e.to_string(),
+					))
+				}
+			},
+			_ => vec![],
+		};
+
+		// Universal arguments are consumed before the script runs so a language argument with the
+		// same alias would never be seen by the script.
+		if let Some(alias) = help
+			.iter()
+			.flat_map(|h| &h.arguments)
+			.flat_map(|a| &a.aliases)
+			.chain(
+				options
+					.iter()
+					.flat_map(|o| o.aliases.iter().map(|a| &a.alias)),
+			)
+			.find(|a| is_universal_arg(a))
+		{
+			return Err(config_error(
+				None,
+				Some("arguments"),
+				&format!(
+					"Cannot load Language from document: language argument '{alias}' shadows a \
+					 universal argument."
+				),
+			));
+		}
+
+		let help = if read_help { help } else { None };
+
 		Ok(Self {
 			name,
 			template_dir,
 			aliases,
 			script_path,
+			output_dirs,
+			naming,
+			options,
 			help,
 		})
 	}
@@ -164,6 +268,8 @@ impl Language
 		temps: &str,
 		aliases: &[String],
 		script: &str,
+		output_dirs: &[(String, String)],
+		naming: Naming,
 		help: Option<LanguageHelp>,
 	) -> Self
 	{
@@ -172,10 +278,30 @@ impl Language
 			template_dir: temps.to_string(),
 			aliases: aliases.to_vec(),
 			script_path: script.to_string(),
+			output_dirs: output_dirs.to_vec(),
+			naming,
+			options: vec![],
 			help,
 		}
 	}
 
+	pub fn output_dir(&self, template: &str) -> Option<&str>
+	{
+		// Template file names take precedence over extensions.
+		let file = get_file_name(template, true).to_lowercase();
+		let ext = get_extention(&file);
+
+		match self.output_dirs.iter().find(|(k, _)| *k == file)
+		{
+			Some((_, d)) => Some(d),
+			None => self
+				.output_dirs
+				.iter()
+				.find(|(k, _)| k == ext)
+				.map(|(_, d)| d.as_str()),
+		}
+	}
+
 	pub fn template_directory(&self) -> String { paths::templates_dir() + &self.template_dir }
 
 	pub fn template_paths(&self) -> Vec<PathBuf>
@@ -209,6 +335,114 @@ impl Language
 		buf
 	}
 
+	pub fn template_types(&self) -> Vec<(String, Vec<String>)>
+	{
+		let mut types: Vec<(String, Vec<String>)> = Vec::new();
+
+		for t in self.template_paths()
+		{
+			let file = get_file_name(&format!("{}", t.display()), true);
+			let stem = get_file_name(&file, false).to_lowercase();
+			let ext = String::from(get_extention(&file));
+
+			match types.iter_mut().find(|(s, _)| *s == stem)
+			{
+				Some((_, exts)) => exts.push(ext),
+				None => types.push((stem, vec![ext])),
+			}
+		}
+
+		for (_, exts) in &mut types
+		{
+			exts.sort();
+		}
+
+		types.sort();
+		types
+	}
+
+	// Returns the file names of the filetype's templates and the macros they use.
+	pub fn template_help(&self, filetype: &str) -> SMResult<(Vec<String>, Vec<String>)>
+	{
+		let templates: Vec<String> = self
+			.template_paths()
+			.iter()
+			.map(|t| format!("{}", t.display()))
+			.filter(|t| get_file_name(t, false).to_lowercase() == filetype)
+			.collect();
+
+		if templates.is_empty()
+		{
+			return Err(Box::new(SMError::TemplateNotFound {
+				language: self.name.clone(),
+				filetype: String::from(filetype),
+			}));
+		}
+
+		let mut macros: Vec<String> = Vec::new();
+
+		for t in &templates
+		{
+			let content = fs::read_to_string(t).unwrap_or_default();
+			let mut rest = content.as_str();
+
+			while let Some(begin) = rest.find('$')
+			{
+				let after = &rest[begin + 1..];
+
+				match after.find('$')
+				{
+					Some(end) if is_valid_name(&after[..end]) =>
+					{
+						let mac = format!("${}$", &after[..end]);
+
+						if !macros.contains(&mac)
+						{
+							macros.push(mac);
+						}
+
+						rest = &after[end + 1..];
+					}
+					// The closing `$` may open the next macro.
+					Some(end) => rest = &after[end..],
+					None => break,
+				}
+			}
+		}
+
+		Ok((
+			templates.iter().map(|t| get_file_name(t, true)).collect(),
+			macros,
+		))
+	}
+	pub fn print_template_help(&self, filetype: &str) -> SMResult<()>
+	{
+		let (templates, macros) = self.template_help(filetype)?;
+
+		println!("{} {filetype} templates:", &self.name);
+
+		for t in &templates
+		{
+			println!("\t{t}");
+		}
+
+		println!("\nMacros used:");
+
+		for mac in &macros
+		{
+			match self
+				.help
+				.as_ref()
+				.and_then(|h| h.macros.iter().find(|m| m.name == *mac))
+			{
+				Some(m) => println!("{m}"),
+				None => println!("\t{mac}"),
+			}
+		}
+
+		Ok(())
+	}
+
 	pub fn print_help(&self)
 	{
 		println!("{} usage:", &self.name);
@@ -220,6 +454,13 @@ impl Language
 			println!("\t{}", alias);
 		}
 
+		println!("Language arguments:");
+
+		for opt in &self.options
+		{
+			println!("\t{opt}");
+		}
+
 		if let Some(h) = &self.help
 		{
 			h.print_help();
@@ -237,8 +478,10 @@ pub fn load_languages(help: bool) -> SMResult<Vec<Language>>
 		Ok(d) => d,
 		Err(e) =>
 		{
-			return Err(box_error(&format!(
-				"Failed reading from directory {lang_dir}: {e}."
+			return Err(Box::new(SMError::io(
+				"Failed reading from directory",
+				&lang_dir,
+				e,
 			)));
 		}
 	};
@@ -288,13 +531,28 @@ pub fn load_languages(help: bool) -> SMResult<Vec<Language>>
 			match Language::from_document(&doc, help)
 			{
 				Ok(l) => Ok(l),
-				Err(e) =>
+				// Errors in the document are located so they point at the offending key.
+				Err(e) => match (e.downcast_ref::<SMError>(), fs::read_to_string(&entrypath))
 				{
-					return Err(make_error(&format!(
+					(
+						Some(SMError::Config {
+							section,
+							key,
+							message,
+						}),
+						Ok(text),
+					) => Err(SMError::ConfigError(Box::new(
+						Diagnostic::new(&entrypath, message).at_cfg_key(
+							&text,
+							section.as_deref(),
+							key.as_deref(),
+						),
+					))),
+					_ => Err(make_error(&format!(
 						"Failed loading language from {}: {e}.",
 						&entrypath
-					)));
-				}
+					))),
+				},
 			}
 		}));
 	}
@@ -307,9 +565,12 @@ pub fn load_languages(help: bool) -> SMResult<Vec<Language>>
 			Err(_) => continue,
 		};
 
-		if let Ok(l) = res
+		// Invalid languages are skipped, but reported on stderr so they do not silently disappear
+		// or break machine readable output.
+		match res
 		{
-			buf.push(l);
+			Ok(l) => buf.push(l),
+			Err(e) => eprintln!("{e}"),
 		}
 	}
 
@@ -16,6 +16,10 @@
 //
 mod help;
 mod language;
+mod naming;
+mod options;
 
 pub use help::*;
 pub use language::*;
+pub use naming::*;
+pub use options::*;
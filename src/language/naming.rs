@@ -0,0 +1,174 @@
+// naming.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use parsecfg::{KeyValue, Section};
+
+use crate::{
+	config_error,
+	name::{as_ascii_name, convert_case, path_to_name, NameCase},
+	SMResult,
+};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Naming
+{
+	pub file_case: NameCase,
+	pub type_case: NameCase,
+	pub keywords: Vec<String>,
+	pub reserved: Vec<String>,
+	pub keyword_escape: String,
+	pub ascii_identifiers: bool,
+}
+impl Naming
+{
+	pub fn from_section(sect: &Section) -> SMResult<Self>
+	{
+		let keyword_escape = match sect.get("keyword_escape")
+		{
+			Some(kv) => match &kv.value
+			{
+				KeyValue::String(s) if s.contains("{}") => s.clone(),
+				KeyValue::String(_) =>
+				{
+					return Err(config_error(
+						None,
+						Some("keyword_escape"),
+						"Cannot load Naming from section: 'keyword_escape' must contain '{}' \
+						 where the escaped name is placed.",
+					))
+				}
+				_ =>
+				{
+					return Err(config_error(
+						None,
+						Some("keyword_escape"),
+						"Cannot load Naming from section: 'keyword_escape' key has wrong value \
+						 type.",
+					))
+				}
+			},
+			_ => String::from("{}_"),
+		};
+
+		let ascii_identifiers = match sect.get("identifiers")
+		{
+			Some(kv) => match &kv.value
+			{
+				KeyValue::String(s) if s.eq_ignore_ascii_case("ascii") => true,
+				KeyValue::String(s) if s.eq_ignore_ascii_case("unicode") => false,
+				_ =>
+				{
+					return Err(config_error(
+						None,
+						Some("identifiers"),
+						"Cannot load Naming from section: 'identifiers' must be either \
+						 \"unicode\" or \"ascii\".",
+					))
+				}
+			},
+			_ => false,
+		};
+
+		Ok(Self {
+			file_case: case_from_section(sect, "file_case")?,
+			type_case: case_from_section(sect, "type_case")?,
+			keywords: array_from_section(sect, "keywords")?,
+			reserved: array_from_section(sect, "reserved")?,
+			keyword_escape,
+			ascii_identifiers,
+		})
+	}
+}
+impl Naming
+{
+	pub fn file_name(&self, name: &str) -> String { convert_case(name, self.file_case) }
+	pub fn type_name(&self, name: &str) -> String
+	{
+		let name = convert_case(name, self.type_case);
+
+		self.escape(&if self.ascii_identifiers
+		{
+			as_ascii_name(&name, '_')
+		}
+		else
+		{
+			path_to_name(&name, '_')
+		})
+	}
+
+	pub fn escape(&self, name: &str) -> String
+	{
+		// Reserved names cannot be escaped by the language (such as `self` in Rust).
+		if self.reserved.iter().any(|k| k == name)
+		{
+			format!("{name}_")
+		}
+		else if self.keywords.iter().any(|k| k == name)
+		{
+			self.keyword_escape.replace("{}", name)
+		}
+		else
+		{
+			String::from(name)
+		}
+	}
+}
+
+fn case_from_section(sect: &Section, key: &str) -> SMResult<NameCase>
+{
+	match sect.get(key)
+	{
+		Some(kv) => match &kv.value
+		{
+			KeyValue::String(s) => match NameCase::from_name(s)
+			{
+				Some(c) => Ok(c),
+				None => Err(config_error(
+					None,
+					Some(key),
+					&format!(
+						"Cannot load Naming from section: '{key}' key has unknown naming \
+						 convention '{s}'."
+					),
+				)),
+			},
+			_ => Err(config_error(
+				None,
+				Some(key),
+				&format!("Cannot load Naming from section: '{key}' key has wrong value type."),
+			)),
+		},
+		_ => Ok(NameCase::Preserve),
+	}
+}
+fn array_from_section(sect: &Section, key: &str) -> SMResult<Vec<String>>
+{
+	match sect.get(key)
+	{
+		Some(kv) => match &kv.value
+		{
+			KeyValue::StringArray(a) => Ok(a.clone()),
+			_ => Err(config_error(
+				None,
+				Some(key),
+				&format!(
+					"Cannot load Naming from section: '{key}' key value type is not an array."
+				),
+			)),
+		},
+		_ => Ok(vec![]),
+	}
+}
@@ -0,0 +1,426 @@
+// options.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use parsecfg::{KeyValue, Section};
+use std::fmt::Display;
+
+use crate::{box_error, name::is_valid_name, SMResult};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OptionKind
+{
+	Flag,
+	Value,
+	List,
+	Enum(Vec<String>),
+}
+impl OptionKind
+{
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		let lower = name.trim().to_lowercase();
+
+		match lower.as_str()
+		{
+			"flag" => Some(Self::Flag),
+			"value" => Some(Self::Value),
+			"list" => Some(Self::List),
+			_ =>
+			{
+				let choices: Vec<String> = lower
+					.strip_prefix("enum:")?
+					.split('|')
+					.map(|c| String::from(c.trim()))
+					.filter(|c| !c.is_empty())
+					.collect();
+
+				if choices.is_empty()
+				{
+					None
+				}
+				else
+				{
+					Some(Self::Enum(choices))
+				}
+			}
+		}
+	}
+}
+impl Display for OptionKind
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Self::Flag => write!(f, "flag"),
+			Self::Value => write!(f, "value"),
+			Self::List => write!(f, "list"),
+			Self::Enum(c) => write!(f, "{}", c.join("|")),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OptionValue
+{
+	Flag(bool),
+	Value(String),
+	List(Vec<String>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptionAlias
+{
+	pub alias: String,
+	// Value and enum aliases may imply a value instead of taking the next argument.
+	pub value: Option<String>,
+}
+impl Display for OptionAlias
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match &self.value
+		{
+			Some(v) => write!(f, "{}={v}", &self.alias),
+			None => write!(f, "{}", &self.alias),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LanguageOption
+{
+	pub name: String,
+	pub kind: OptionKind,
+	pub aliases: Vec<OptionAlias>,
+	pub default: String,
+	pub info: String,
+}
+impl Display for LanguageOption
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		let aliases: Vec<String> = self.aliases.iter().map(|a| a.to_string()).collect();
+
+		write!(
+			f,
+			"\t{} <{}> - {}",
+			aliases.join(" | "),
+			&self.kind,
+			&self.info
+		)?;
+
+		if !self.default.is_empty()
+		{
+			write!(f, " (default: {})", &self.default)?;
+		}
+
+		Ok(())
+	}
+}
+impl LanguageOption
+{
+	pub fn from_section(sect: &Section) -> SMResult<Vec<Self>>
+	{
+		let arr = match sect.get("arguments")
+		{
+			Some(kv) => match &kv.value
+			{
+				KeyValue::StringArray(a) if a.len() % 5 == 0 => a.clone(),
+				KeyValue::StringArray(_) =>
+				{
+					return Err(box_error(
+						"Cannot load options from section: 'arguments' array must contain groups \
+						 of five strings.",
+					))
+				}
+				_ =>
+				{
+					return Err(box_error(
+						"Cannot load options from section: 'arguments' key value type is not an \
+						 array.",
+					))
+				}
+			},
+			_ => return Ok(vec![]),
+		};
+
+		let mut options: Vec<Self> = Vec::new();
+
+		for o in arr.chunks(5)
+		{
+			let option = Self::new(&o[0], &o[1], &o[2], &o[3], &o[4])?;
+
+			if options.iter().any(|p| p.name == option.name)
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the option '{}' is defined more than once.",
+					&option.name
+				)));
+			}
+			if let Some(a) = option
+				.aliases
+				.iter()
+				.find(|a| options.iter().any(|p| p.alias(&a.alias).is_some()))
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the alias '{}' is used by more than one \
+					 option.",
+					&a.alias
+				)));
+			}
+
+			options.push(option);
+		}
+
+		Ok(options)
+	}
+
+	pub fn new(name: &str, kind: &str, aliases: &str, default: &str, info: &str) -> SMResult<Self>
+	{
+		let name = name.trim();
+
+		// Option names are used as keys in the `SMOptions` lua table.
+		if !is_valid_name(name)
+		{
+			return Err(box_error(&format!(
+				"Cannot load options from section: '{name}' is not a valid option name."
+			)));
+		}
+
+		let kind = match OptionKind::from_name(kind)
+		{
+			Some(k) => k,
+			None =>
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the option '{name}' has unknown kind \
+					 '{kind}'; expected flag, value, list or enum:[choice]|[choice]."
+				)))
+			}
+		};
+
+		let mut option = Self {
+			name: String::from(name),
+			kind,
+			aliases: vec![],
+			default: String::from(default.trim()),
+			info: String::from(info),
+		};
+
+		for alias in aliases
+			.split(',')
+			.map(|a| a.trim())
+			.filter(|a| !a.is_empty())
+		{
+			let (alias, value) = match alias.split_once('=')
+			{
+				Some((a, v)) => (a.trim(), Some(option.check_value(v.trim())?)),
+				None => (alias, None),
+			};
+
+			if !alias.starts_with('-')
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the alias '{alias}' of option '{name}' \
+					 must start with '-'."
+				)));
+			}
+			if value.is_some() && matches!(option.kind, OptionKind::Flag | OptionKind::List)
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the alias '{alias}' of option '{name}' \
+					 cannot imply a value for a {} option.",
+					&option.kind
+				)));
+			}
+
+			option.aliases.push(OptionAlias {
+				alias: alias.to_lowercase(),
+				value,
+			});
+		}
+
+		if option.aliases.is_empty()
+		{
+			return Err(box_error(&format!(
+				"Cannot load options from section: the option '{name}' has no aliases."
+			)));
+		}
+
+		match &option.kind
+		{
+			OptionKind::Flag if !matches!(option.default.as_str(), "" | "true" | "false") =>
+			{
+				return Err(box_error(&format!(
+					"Cannot load options from section: the default of flag option '{name}' must \
+					 be true or false."
+				)))
+			}
+			OptionKind::Enum(_) if !option.default.is_empty() =>
+			{
+				option.default = option.check_value(&option.default)?;
+			}
+			_ =>
+			{}
+		}
+
+		Ok(option)
+	}
+}
+impl LanguageOption
+{
+	pub fn alias(&self, arg: &str) -> Option<&OptionAlias>
+	{
+		let lower = arg.to_lowercase();
+		self.aliases.iter().find(|a| a.alias == lower)
+	}
+
+	// Returns the value as written in the enum choices, or an error if it is not one of them.
+	fn check_value(&self, value: &str) -> SMResult<String>
+	{
+		match &self.kind
+		{
+			OptionKind::Enum(choices) =>
+			{
+				match choices.iter().find(|c| c.eq_ignore_ascii_case(value))
+				{
+					Some(c) => Ok(c.clone()),
+					None => Err(box_error(&format!(
+						"'{value}' is not a valid value for the {} argument; expected one of {}.",
+						&self.name,
+						choices.join(", ")
+					))),
+				}
+			}
+			_ => Ok(String::from(value)),
+		}
+	}
+
+	pub fn default_value(&self) -> OptionValue
+	{
+		match &self.kind
+		{
+			OptionKind::Flag => OptionValue::Flag(self.default == "true"),
+			OptionKind::Value | OptionKind::Enum(_) => OptionValue::Value(self.default.clone()),
+			OptionKind::List => OptionValue::List(
+				self.default
+					.split(',')
+					.map(|s| String::from(s.trim()))
+					.filter(|s| !s.is_empty())
+					.collect(),
+			),
+		}
+	}
+}
+
+// Parses the language arguments into the declared options, every argument must belong to one.
+pub fn parse_options(
+	options: &[LanguageOption],
+	args: &[String],
+) -> SMResult<Vec<(String, OptionValue)>>
+{
+	let mut values: Vec<Option<OptionValue>> = vec![None; options.len()];
+	let mut i = 0;
+
+	while i < args.len()
+	{
+		let arg = &args[i];
+
+		let (index, alias) = match options
+			.iter()
+			.enumerate()
+			.find_map(|(oi, o)| o.alias(arg).map(|a| (oi, a)))
+		{
+			Some(f) => f,
+			None if arg.starts_with('-') =>
+			{
+				return Err(box_error(&format!("Unknown language argument '{arg}'.")))
+			}
+			None => return Err(box_error(&format!("Unexpected argument '{arg}'."))),
+		};
+		let option = &options[index];
+
+		i += 1;
+
+		match &option.kind
+		{
+			OptionKind::Flag => values[index] = Some(OptionValue::Flag(true)),
+			OptionKind::List =>
+			{
+				let mut list = match values[index].take()
+				{
+					Some(OptionValue::List(l)) => l,
+					_ => vec![],
+				};
+				let start = i;
+
+				// Lists continue until the next argument.
+				while i < args.len() && !args[i].starts_with('-')
+				{
+					list.push(args[i].clone());
+					i += 1;
+				}
+
+				if i == start
+				{
+					return Err(box_error(&format!(
+						"The {arg} argument expects at least one value."
+					)));
+				}
+
+				values[index] = Some(OptionValue::List(list));
+			}
+			OptionKind::Value | OptionKind::Enum(_) =>
+			{
+				let value = match &alias.value
+				{
+					Some(v) => v.clone(),
+					None => match args.get(i)
+					{
+						Some(v) if !v.starts_with('-') =>
+						{
+							i += 1;
+							option.check_value(v)?
+						}
+						_ =>
+						{
+							return Err(box_error(&format!("The {arg} argument expects a value.")))
+						}
+					},
+				};
+
+				match &values[index]
+				{
+					Some(OptionValue::Value(prev)) if *prev != value =>
+					{
+						return Err(box_error(&format!(
+							"The {} argument was given conflicting values '{prev}' and '{value}'.",
+							&option.name
+						)))
+					}
+					_ => values[index] = Some(OptionValue::Value(value)),
+				}
+			}
+		}
+	}
+
+	Ok(options
+		.iter()
+		.zip(values)
+		.map(|(o, v)| (o.name.clone(), v.unwrap_or_else(|| o.default_value())))
+		.collect())
+}
@@ -21,9 +21,13 @@ extern crate parsecfg;
 mod error;
 
 pub mod app;
+pub mod diagnostic;
+pub mod diff;
 pub mod envpath;
 pub mod language;
 pub mod name;
 pub mod paths;
+pub mod project;
+pub mod vars;
 
 pub use error::*;
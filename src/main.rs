@@ -14,13 +14,6 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-fn main() -> Result<(), ()>
-{
-	if let Err(e) = srcmake::app::run_srcmake()
-	{
-		println!("Srcmake did not run successfully: {e}");
-		return Err(());
-	}
+use std::process::ExitCode;
 
-	Ok(())
-}
+fn main() -> ExitCode { srcmake::app::run_srcmake() }
@@ -14,196 +14,623 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-use crate::paths::get_file_name;
+use std::{error::Error, fmt};
+use unicode_ident::{is_xid_continue, is_xid_start};
 
-pub fn is_valid_file_name(name: &str) -> bool
+use crate::paths::{get_file_name, unify_separators};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NameCase
 {
-	if name.is_empty()
+	#[default]
+	Preserve,
+	Pascal,
+	Camel,
+	Snake,
+	ScreamingSnake,
+	Kebab,
+}
+impl NameCase
+{
+	pub fn from_name(case: &str) -> Option<Self>
 	{
-		return false;
+		match case.trim().to_lowercase().as_str()
+		{
+			"preserve" | "" => Some(Self::Preserve),
+			"pascal" => Some(Self::Pascal),
+			"camel" => Some(Self::Camel),
+			"snake" => Some(Self::Snake),
+			"screaming_snake" | "upper_snake" => Some(Self::ScreamingSnake),
+			"kebab" => Some(Self::Kebab),
+			_ => None,
+		}
 	}
+}
 
-	for c in name.chars()
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathPlatform
+{
+	Windows,
+	Posix,
+	Portable,
+}
+impl PathPlatform
+{
+	pub fn host() -> Self
 	{
-		if (c as i32) < 32
+		if cfg!(windows)
 		{
-			return false;
+			Self::Windows
 		}
+		else
+		{
+			Self::Posix
+		}
+	}
+}
+impl fmt::Display for PathPlatform
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Self::Windows => write!(f, "Windows"),
+			Self::Posix => write!(f, "POSIX"),
+			Self::Portable => write!(f, "portable"),
+		}
+	}
+}
 
-		match c
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvalidPathReason
+{
+	Empty,
+	ControlCharacter,
+	ReservedCharacter,
+	ReservedName,
+	TrailingDotOrSpace,
+	InvalidDrive,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidPath
+{
+	pub path: String,
+	pub platform: PathPlatform,
+	pub reason: InvalidPathReason,
+	pub character: Option<char>,
+	// The char index into `path` where the problem was found.
+	pub position: usize,
+}
+impl fmt::Display for InvalidPath
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(
+			f,
+			"'{}' is not a valid {} path: ",
+			&self.path, self.platform
+		)?;
+
+		match (&self.reason, self.character)
 		{
-			'<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' | '$' | '\'' | '&' =>
+			(InvalidPathReason::Empty, _) => write!(f, "it is empty"),
+			(InvalidPathReason::ControlCharacter, Some(c)) => write!(
+				f,
+				"control character {:#04x} at position {}",
+				c as u32, self.position
+			),
+			(InvalidPathReason::ReservedCharacter, Some(c)) =>
 			{
-				return false
+				write!(f, "reserved character '{c}' at position {}", self.position)
 			}
-			_ => continue,
-		};
+			(InvalidPathReason::ReservedName, _) => write!(
+				f,
+				"the name at position {} is reserved on Windows",
+				self.position
+			),
+			(InvalidPathReason::TrailingDotOrSpace, _) => write!(
+				f,
+				"the name ending at position {} ends with a dot or space",
+				self.position
+			),
+			(InvalidPathReason::InvalidDrive, _) =>
+			{
+				write!(f, "invalid drive at position {}", self.position)
+			}
+			(_, None) => write!(f, "invalid character at position {}", self.position),
+		}
 	}
-
-	true
 }
-pub fn is_valid_file_path(path: &str) -> bool
+impl Error for InvalidPath {}
+
+const WINDOWS_RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+	"CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+	"COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// `offset` is the char index of `name` within the full path, used for error positions.
+fn check_file_name(
+	name: &str,
+	path: &str,
+	offset: usize,
+	platform: PathPlatform,
+) -> Result<(), InvalidPath>
 {
-	if path.is_empty()
+	let error = |reason, character, position| InvalidPath {
+		path: String::from(path),
+		platform,
+		reason,
+		character,
+		position,
+	};
+
+	if name.is_empty()
 	{
-		return false;
+		return Err(error(InvalidPathReason::Empty, None, offset));
 	}
 
-	let mut p = path.replace("\\", "/");
-	p = if p.len() >= 4 && &p[..4] == "//?/"
+	let windows = platform != PathPlatform::Posix;
+
+	for (i, c) in name.chars().enumerate()
 	{
-		String::from(&p[..4])
+		if c == '\0' || (windows && (c as u32) < 32)
+		{
+			return Err(error(
+				InvalidPathReason::ControlCharacter,
+				Some(c),
+				offset + i,
+			));
+		}
+		if c == '/' || c == '\\' || (windows && WINDOWS_RESERVED_CHARS.contains(&c))
+		{
+			return Err(error(
+				InvalidPathReason::ReservedCharacter,
+				Some(c),
+				offset + i,
+			));
+		}
 	}
-	else
-	{
-		path.to_string()
-	};
 
-	while !p.is_empty() && &p[0..1] == "/"
+	if windows && name != "." && name != ".."
 	{
-		p.remove(0);
+		// Reserved device names are reserved with any extension, such as `NUL.txt`.
+		let stem = match name.find('.')
+		{
+			Some(i) => &name[..i],
+			None => name,
+		};
+
+		if WINDOWS_RESERVED_NAMES
+			.iter()
+			.any(|r| r.eq_ignore_ascii_case(stem.trim_end()))
+		{
+			return Err(error(InvalidPathReason::ReservedName, None, offset));
+		}
+		if name.ends_with(['.', ' '])
+		{
+			return Err(error(
+				InvalidPathReason::TrailingDotOrSpace,
+				None,
+				offset + name.chars().count() - 1,
+			));
+		}
 	}
 
-	// if name is empty here, it must have been a root path ("/" | "\")
-	if p.is_empty()
+	Ok(())
+}
+
+pub fn validate_file_name(name: &str, platform: PathPlatform) -> Result<(), InvalidPath>
+{
+	check_file_name(name, name, 0, platform)
+}
+pub fn validate_file_path(path: &str, platform: PathPlatform) -> Result<(), InvalidPath>
+{
+	if path.is_empty()
 	{
-		return true;
+		return Err(InvalidPath {
+			path: String::new(),
+			platform,
+			reason: InvalidPathReason::Empty,
+			character: None,
+			position: 0,
+		});
 	}
 
-	match p.find(':')
+	// Srcmake treats both slashes as separators on every platform.
+	let unified = unify_separators(path);
+	let mut rest: &str = &unified;
+	let mut offset = 0;
+
+	if platform == PathPlatform::Windows
 	{
-		Some(i) =>
+		// Skip the `\\?\` verbatim prefix and a drive letter.
+		if let Some(r) = rest.strip_prefix("//?/")
 		{
-			let fc = p.chars().next().unwrap();
+			rest = r;
+			offset += 4;
+		}
 
-			if i != 1 || ((fc < 'a' || fc > 'z') && (fc < 'A' || fc > 'Z'))
-			{
-				return false;
-			}
+		let mut chars = rest.chars();
 
-			p = if p.len() > 2 && (&p[2..3] == "\\" || &p[2..3] == "/")
+		if let (Some(d), Some(':')) = (chars.next(), chars.next())
+		{
+			if !d.is_ascii_alphabetic()
 			{
-				String::from(&p[3..])
+				return Err(InvalidPath {
+					path: String::from(path),
+					platform,
+					reason: InvalidPathReason::InvalidDrive,
+					character: Some(d),
+					position: offset,
+				});
 			}
-			else
-			{
-				String::from(&p[2..])
-			};
-		}
-		_ =>
-		{}
-	};
 
-	// if name is empty here, it must have been a root path ("C:" | "D:\")
-	if p.is_empty()
-	{
-		return true;
+			rest = &rest[2..];
+			offset += 2;
+		}
 	}
 
-	let split = p.split("/");
-	let vec: Vec<&str> = split.collect();
-
-	for f in vec
+	for component in rest.split('/')
 	{
-		if !is_valid_file_name(f)
+		// Empty components come from root and repeated separators.
+		if !component.is_empty()
 		{
-			return false;
+			check_file_name(component, path, offset, platform)?;
 		}
+
+		offset += component.chars().count() + 1;
 	}
 
-	true
+	Ok(())
 }
 
+pub fn is_valid_file_name(name: &str) -> bool
+{
+	validate_file_name(name, PathPlatform::host()).is_ok()
+}
+pub fn is_valid_file_path(path: &str) -> bool
+{
+	validate_file_path(path, PathPlatform::host()).is_ok()
+}
+
+fn is_name_start(c: char) -> bool { c == '_' || is_xid_start(c) }
+fn is_name_continue(c: char) -> bool { is_xid_continue(c) }
+
 pub fn is_valid_name(name: &str) -> bool
+{
+	let mut chars = name.chars();
+
+	match chars.next()
+	{
+		Some(c) if is_name_start(c) => chars.all(is_name_continue),
+		_ => false,
+	}
+}
+
+pub fn as_valid_name(name: &str, repl: char) -> String
 {
 	if name.is_empty()
 	{
-		return false;
+		return repl.to_string();
 	}
 
-	let mut first = true;
+	// Work on chars rather than byte offsets so multi-byte characters are replaced whole.
+	name.chars()
+		.enumerate()
+		.map(|(i, c)| {
+			if (i == 0 && is_name_start(c)) || (i > 0 && is_name_continue(c))
+			{
+				c
+			}
+			else
+			{
+				repl
+			}
+		})
+		.collect()
+}
+pub fn as_ascii_name(name: &str, repl: char) -> String
+{
+	as_valid_name(&transliterate(name), repl)
+		.chars()
+		.map(|c| {
+			if c.is_ascii()
+			{
+				c
+			}
+			else
+			{
+				repl
+			}
+		})
+		.collect()
+}
+
+pub fn path_to_name(path: &str, repl: char) -> String
+{
+	if path.is_empty()
+	{
+		return repl.to_string();
+	}
+	if is_valid_name(path)
+	{
+		return String::from(path);
+	}
 
-	let name = String::from(name).to_lowercase();
+	as_valid_name(&get_file_name(path, false), repl)
+}
+
+pub fn transliterate(name: &str) -> String
+{
+	// Latin letters with diacritics and their ASCII base letters.
+	const TABLE: &[(&str, &str)] = &[
+		("ÀÁÂÃÄÅĀĂĄ", "A"),
+		("àáâãäåāăą", "a"),
+		("ÇĆĈĊČ", "C"),
+		("çćĉċč", "c"),
+		("ĎĐÐ", "D"),
+		("ďđð", "d"),
+		("ÈÉÊËĒĔĖĘĚ", "E"),
+		("èéêëēĕėęě", "e"),
+		("ĜĞĠĢ", "G"),
+		("ĝğġģ", "g"),
+		("ĤĦ", "H"),
+		("ĥħ", "h"),
+		("ÌÍÎÏĨĪĬĮİ", "I"),
+		("ìíîïĩīĭįı", "i"),
+		("Ĵ", "J"),
+		("ĵ", "j"),
+		("Ķ", "K"),
+		("ķĸ", "k"),
+		("ĹĻĽĿŁ", "L"),
+		("ĺļľŀł", "l"),
+		("ÑŃŅŇ", "N"),
+		("ñńņňŉ", "n"),
+		("ÒÓÔÕÖØŌŎŐ", "O"),
+		("òóôõöøōŏő", "o"),
+		("ŔŖŘ", "R"),
+		("ŕŗř", "r"),
+		("ŚŜŞŠ", "S"),
+		("śŝşš", "s"),
+		("ŢŤŦ", "T"),
+		("ţťŧ", "t"),
+		("ÙÚÛÜŨŪŬŮŰŲ", "U"),
+		("ùúûüũūŭůűų", "u"),
+		("Ŵ", "W"),
+		("ŵ", "w"),
+		("ÝŶŸ", "Y"),
+		("ýÿŷ", "y"),
+		("ŹŻŽ", "Z"),
+		("źżž", "z"),
+		("ß", "ss"),
+		("Æ", "AE"),
+		("æ", "ae"),
+		("Œ", "OE"),
+		("œ", "oe"),
+		("Þ", "TH"),
+		("þ", "th"),
+	];
+
+	let mut result = String::with_capacity(name.len());
 
 	for c in name.chars()
 	{
-		if first
+		if c.is_ascii()
+		{
+			result.push(c);
+		}
+		else if let Some((_, r)) = TABLE.iter().find(|(from, _)| from.contains(c))
 		{
-			if (c < 'a' || c > 'z') && c != '_'
+			result.push_str(r);
+		}
+		else
+		{
+			result.push(c);
+		}
+	}
+
+	result
+}
+
+pub fn split_words(name: &str) -> Vec<String>
+{
+	let chars: Vec<char> = name.chars().collect();
+	let mut words: Vec<String> = Vec::new();
+	let mut word = String::new();
+
+	for (i, &c) in chars.iter().enumerate()
+	{
+		if !c.is_alphanumeric()
+		{
+			if !word.is_empty()
 			{
-				return false;
+				words.push(std::mem::take(&mut word));
 			}
 
-			first = false;
+			continue;
 		}
-		else
+
+		if !word.is_empty() && c.is_uppercase()
 		{
-			if (c < 'a' || c > 'z') && (c < '0' || c > '9') && c != '_'
+			let prev = chars[i - 1];
+			let next_lower = i + 1 < chars.len() && chars[i + 1].is_lowercase();
+
+			// Split "myWidget" and "Vec3D" before the capital, and "HTTPServer" before "Server".
+			if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower)
 			{
-				return false;
+				words.push(std::mem::take(&mut word));
 			}
 		}
+
+		word.push(c);
 	}
 
-	true
-}
+	if !word.is_empty()
+	{
+		words.push(word);
+	}
 
-pub fn as_valid_name(name: &str, repl: char) -> String
+	words
+}
+pub fn convert_case(name: &str, case: NameCase) -> String
 {
-	if name.is_empty()
+	let words = split_words(name);
+
+	if case == NameCase::Preserve || words.is_empty()
 	{
-		return repl.to_string();
+		return String::from(name);
 	}
 
-	let mut result = String::from(name);
+	let capitalise = |w: &String| {
+		let mut c = w.chars();
 
-	let mut first = true;
-	let mut i: usize = 0;
-	let mut indicies: Vec<usize> = Vec::new();
+		match c.next()
+		{
+			Some(f) => f
+				.to_uppercase()
+				.chain(c.flat_map(|l| l.to_lowercase()))
+				.collect(),
+			None => String::new(),
+		}
+	};
 
-	let lo = result.to_lowercase();
+	match case
+	{
+		NameCase::Pascal => words.iter().map(capitalise).collect(),
+		NameCase::Camel => words
+			.iter()
+			.enumerate()
+			.map(|(i, w)| {
+				if i == 0
+				{
+					w.to_lowercase()
+				}
+				else
+				{
+					capitalise(w)
+				}
+			})
+			.collect(),
+		NameCase::Snake => words.join("_").to_lowercase(),
+		NameCase::ScreamingSnake => words.join("_").to_uppercase(),
+		NameCase::Kebab => words.join("-").to_lowercase(),
+		NameCase::Preserve => String::from(name),
+	}
+}
+
+// Expands brace groups such as `ui/{Button,Label}` into each name, groups without a comma or
+// a closing brace are left as they are.
+pub fn expand_braces(name: &str) -> Vec<String>
+{
+	let bytes = name.as_bytes();
+	let mut start = 0;
 
-	for c in lo.chars()
+	while let Some(open) = name[start..].find('{').map(|i| i + start)
 	{
-		if first
+		let mut depth = 0;
+		let mut bounds = vec![open];
+		let mut close = None;
+
+		for (i, b) in bytes.iter().enumerate().skip(open)
 		{
-			if (c < 'a' || c > 'z') && c != '_'
+			match b
 			{
-				indicies.push(i);
+				b'{' => depth += 1,
+				b'}' =>
+				{
+					depth -= 1;
+
+					if depth == 0
+					{
+						close = Some(i);
+						break;
+					}
+				}
+				b',' if depth == 1 => bounds.push(i),
+				_ =>
+				{}
 			}
+		}
 
-			first = false;
+		let close = match close
+		{
+			Some(c) => c,
+			None => break,
+		};
+
+		if bounds.len() == 1
+		{
+			start = open + 1;
+			continue;
 		}
-		else
+
+		bounds.push(close);
+
+		let (prefix, suffix) = (&name[..open], &name[close + 1..]);
+		let mut names: Vec<String> = Vec::new();
+
+		// Alternatives may contain groups of their own, as may the rest of the name.
+		for w in bounds.windows(2)
 		{
-			if (c < 'a' || c > 'z') && (c < '0' || c > '9') && c != '_'
+			for n in expand_braces(&format!("{prefix}{}{suffix}", &name[w[0] + 1..w[1]]))
 			{
-				indicies.push(i);
+				if !names.contains(&n)
+				{
+					names.push(n);
+				}
 			}
 		}
 
-		i += 1;
-	}
-
-	for ind in indicies
-	{
-		result.remove(ind);
-		result.insert(ind, repl);
+		return names;
 	}
 
-	result
+	vec![String::from(name)]
 }
 
-pub fn path_to_name(path: &str, repl: char) -> String
+#[cfg(test)]
+mod tests
 {
-	if path.is_empty()
+	use quickcheck::quickcheck;
+
+	use super::*;
+
+	quickcheck! {
+		fn path_to_name_is_valid(path: String) -> bool { is_valid_name(&path_to_name(&path, '_')) }
+		fn as_ascii_name_is_valid_ascii(name: String) -> bool
+		{
+			let result = as_ascii_name(&name, '_');
+
+			is_valid_name(&result) && result.is_ascii()
+		}
+	}
+
+	#[test]
+	fn accepts_non_ascii_names()
 	{
-		return repl.to_string();
+		assert!(is_valid_name("Größe"));
+		assert!(is_valid_name("Ñandú"));
+		assert!(is_valid_name("日本語"));
+		assert!(!is_valid_name("1Größe"));
+		assert!(!is_valid_name("Größe-Ñandú"));
 	}
-	if is_valid_name(path)
+
+	#[test]
+	fn replaces_whole_characters()
 	{
-		return String::from(path);
+		assert_eq!(as_valid_name("Größe-Ñandú", '_'), "Größe_Ñandú");
+		assert_eq!(as_valid_name("1Größe", '_'), "_Größe");
+		assert_eq!(as_valid_name("Ñandú 日本", '_'), "Ñandú_日本");
+		assert_eq!(path_to_name("src/Größe Ñandú.rs", '_'), "Größe_Ñandú");
+		assert_eq!(path_to_name("src\\Ñandú.rs", '_'), "Ñandú");
 	}
 
-	as_valid_name(&get_file_name(path, false), '_')
+	#[test]
+	fn transliterates_to_ascii()
+	{
+		assert_eq!(as_ascii_name("Größe", '_'), "Grosse");
+		assert_eq!(as_ascii_name("Ñandú", '_'), "Nandu");
+		assert_eq!(as_ascii_name("Größe日本", '_'), "Grosse__");
+	}
 }
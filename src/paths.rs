@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License along with this program.
 // If not, see <https://www.gnu.org/licenses/>.
 //
-use std::{env, path::PathBuf};
+use std::{
+	env,
+	path::{Component, Path, PathBuf},
+};
 
 pub fn executable_dir() -> String
 {
@@ -121,3 +124,122 @@ pub fn get_directory(path: &str) -> String
 }
 
 pub fn unify_separators(path: &str) -> String { path.replace('\\', "/") }
+
+pub fn relative_path(path: &str, base: &str) -> String
+{
+	let path: Vec<Component> = Path::new(path).components().collect();
+	let base: Vec<Component> = Path::new(base).components().collect();
+
+	let common = path
+		.iter()
+		.zip(base.iter())
+		.take_while(|(a, b)| a == b)
+		.count();
+
+	let mut result = PathBuf::new();
+
+	for _ in common..base.len()
+	{
+		result.push("..");
+	}
+	for c in &path[common..]
+	{
+		result.push(c.as_os_str());
+	}
+
+	unify_separators(&format!("{}", result.display()))
+}
+
+pub fn normalize_path(path: &Path) -> PathBuf
+{
+	let mut result = PathBuf::new();
+
+	for c in path.components()
+	{
+		match c
+		{
+			Component::CurDir =>
+			{}
+			// Only a directory name is removed, `..` cannot climb above a root so `/..` stays at
+			// `/`, and the `..` of a relative path are kept.
+			Component::ParentDir => match result.components().next_back()
+			{
+				Some(Component::Normal(_)) =>
+				{
+					result.pop();
+				}
+				Some(Component::RootDir) =>
+				{}
+				_ => result.push(".."),
+			},
+			_ => result.push(c.as_os_str()),
+		}
+	}
+
+	result
+}
+pub fn resolve_path(path: &Path) -> PathBuf
+{
+	// Canonicalise the deepest existing ancestor to resolve symbolic links, the rest of the path
+	// may not have been created yet.
+	let path = normalize_path(path);
+	let mut existing = path.clone();
+	let mut rest: Vec<PathBuf> = Vec::new();
+
+	loop
+	{
+		if let Ok(canon) = existing.canonicalize()
+		{
+			let mut result = canon;
+
+			for r in rest.iter().rev()
+			{
+				result.push(r);
+			}
+
+			return result;
+		}
+
+		match (existing.file_name(), existing.parent())
+		{
+			(Some(name), Some(parent)) =>
+			{
+				rest.push(PathBuf::from(name));
+				existing = parent.to_path_buf();
+			}
+			_ => return path,
+		}
+	}
+}
+pub fn is_within(path: &Path, root: &Path) -> bool
+{
+	resolve_path(path).starts_with(resolve_path(root))
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn normalize_path_keeps_leading_parents()
+	{
+		assert_eq!(normalize_path(Path::new("../../x")), Path::new("../../x"));
+		assert_eq!(normalize_path(Path::new("a/../../x")), Path::new("../x"));
+		assert_eq!(normalize_path(Path::new("./a/b/../c")), Path::new("a/c"));
+		assert_eq!(normalize_path(Path::new("/a/../../x")), Path::new("/x"));
+	}
+
+	#[test]
+	fn is_within_refuses_climbing_out()
+	{
+		let root = env::temp_dir();
+
+		assert!(is_within(&root.join("a/../b"), &root));
+		assert!(!is_within(&root.join("a/../../b"), &root));
+		assert!(!is_within(
+			Path::new("../../x"),
+			&env::current_dir().unwrap()
+		));
+	}
+}
@@ -0,0 +1,257 @@
+// project.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use std::{fs, path::PathBuf};
+
+use crate::{
+	name::as_valid_name,
+	paths::{get_extention, get_file_name, unify_separators},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProjectKind
+{
+	Cargo,
+	CMake,
+	MSBuild,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Project
+{
+	pub kind: ProjectKind,
+	pub root: String,
+	pub name: String,
+	pub version: String,
+	pub crate_name: String,
+	pub root_namespace: String,
+}
+impl Project
+{
+	pub fn find(dir: &str) -> Option<Self>
+	{
+		let mut path = PathBuf::from(dir);
+
+		loop
+		{
+			let root = unify_separators(&format!("{}", path.display()));
+
+			if let Some(p) = Self::from_cargo(&root)
+				.or_else(|| Self::from_csproj(&root))
+				.or_else(|| Self::from_cmake(&root))
+			{
+				return Some(p);
+			}
+
+			if !path.pop()
+			{
+				return None;
+			}
+		}
+	}
+
+	pub fn from_cargo(dir: &str) -> Option<Self>
+	{
+		let text = fs::read_to_string(format!("{}/Cargo.toml", dir.trim_end_matches('/'))).ok()?;
+
+		let table = text.parse::<toml::Table>().ok()?;
+		let package = table.get("package").and_then(|p| p.as_table());
+		let name = package
+			.and_then(|p| p.get("name"))
+			.and_then(|n| n.as_str())
+			.unwrap_or_default()
+			.to_string();
+		let lib_name = table
+			.get("lib")
+			.and_then(|l| l.get("name"))
+			.and_then(|n| n.as_str())
+			.unwrap_or_default()
+			.to_string();
+
+		// Members may inherit the version from the workspace with `version.workspace = true`.
+		let version = match package.and_then(|p| p.get("version"))
+		{
+			Some(toml::Value::String(v)) => v.clone(),
+			Some(toml::Value::Table(t))
+				if t.get("workspace") == Some(&toml::Value::Boolean(true)) =>
+			{
+				workspace_version(dir).unwrap_or_default()
+			}
+			_ => String::new(),
+		};
+
+		// Virtual workspace manifests have no package, keep searching upwards.
+		if name.is_empty()
+		{
+			return None;
+		}
+
+		let crate_name = if lib_name.is_empty()
+		{
+			name.replace('-', "_")
+		}
+		else
+		{
+			lib_name
+		};
+
+		Some(Self {
+			kind: ProjectKind::Cargo,
+			root: dir.to_string(),
+			root_namespace: crate_name.clone(),
+			crate_name,
+			name,
+			version,
+		})
+	}
+
+	pub fn from_cmake(dir: &str) -> Option<Self>
+	{
+		let text =
+			fs::read_to_string(format!("{}/CMakeLists.txt", dir.trim_end_matches('/'))).ok()?;
+
+		let lower = text.to_ascii_lowercase();
+		let mut start = None;
+		let mut from = 0;
+
+		while let Some(i) = lower[from..].find("project")
+		{
+			let i = from + i;
+			let prev = lower[..i].chars().next_back();
+			let rest = lower[i + "project".len()..].trim_start();
+
+			if rest.starts_with('(') && !prev.is_some_and(|c| c.is_alphanumeric() || c == '_')
+			{
+				start = Some(i + "project".len());
+				break;
+			}
+
+			from = i + "project".len();
+		}
+
+		let start = start?;
+		let open = text[start..].find('(')? + start + 1;
+		let close = text[open..].find(')')? + open;
+
+		let tokens: Vec<&str> = text[open..close]
+			.split_whitespace()
+			.map(|t| t.trim_matches('"'))
+			.collect();
+
+		let name = tokens.first()?.to_string();
+		let version = match tokens
+			.iter()
+			.position(|t| t.eq_ignore_ascii_case("VERSION"))
+		{
+			Some(i) if i + 1 < tokens.len() => tokens[i + 1].to_string(),
+			_ => String::new(),
+		};
+
+		Some(Self {
+			kind: ProjectKind::CMake,
+			root: dir.to_string(),
+			crate_name: as_valid_name(&name, '_'),
+			root_namespace: as_valid_name(&name, '_'),
+			name,
+			version,
+		})
+	}
+
+	pub fn from_csproj(dir: &str) -> Option<Self>
+	{
+		let mut projects: Vec<String> = fs::read_dir(dir)
+			.ok()?
+			.filter_map(|e| e.ok())
+			.map(|e| unify_separators(&format!("{}", e.path().display())))
+			.filter(|p| get_extention(p).eq_ignore_ascii_case("csproj"))
+			.collect();
+
+		projects.sort();
+
+		let path = projects.first()?;
+		let text = fs::read_to_string(path).ok()?;
+
+		let name = match xml_element(&text, "AssemblyName")
+		{
+			Some(n) => n,
+			_ => get_file_name(path, false),
+		};
+		// As with MSBuild, the root namespace defaults to the project name.
+		let root_namespace = match xml_element(&text, "RootNamespace")
+		{
+			Some(n) => n,
+			_ => name
+				.split('.')
+				.map(|s| as_valid_name(s, '_'))
+				.collect::<Vec<String>>()
+				.join("."),
+		};
+
+		Some(Self {
+			kind: ProjectKind::MSBuild,
+			root: dir.to_string(),
+			crate_name: as_valid_name(&name, '_'),
+			version: xml_element(&text, "Version").unwrap_or_default(),
+			name,
+			root_namespace,
+		})
+	}
+}
+
+// The version in `[workspace.package]` of the nearest workspace manifest above a member.
+fn workspace_version(dir: &str) -> Option<String>
+{
+	let mut path = PathBuf::from(dir);
+
+	while path.pop()
+	{
+		let text = match fs::read_to_string(path.join("Cargo.toml"))
+		{
+			Ok(t) => t,
+			_ => continue,
+		};
+		let table = text.parse::<toml::Table>().ok()?;
+
+		if let Some(workspace) = table.get("workspace")
+		{
+			return workspace
+				.get("package")
+				.and_then(|p| p.get("version"))
+				.and_then(|v| v.as_str())
+				.map(String::from);
+		}
+	}
+
+	None
+}
+fn xml_element(text: &str, tag: &str) -> Option<String>
+{
+	let open = format!("<{tag}>");
+	let close = format!("</{tag}>");
+
+	let begin = text.find(&open)? + open.len();
+	let end = text[begin..].find(&close)? + begin;
+	let value = text[begin..end].trim();
+
+	if value.is_empty() || value.contains("$(")
+	{
+		None
+	}
+	else
+	{
+		Some(value.to_string())
+	}
+}
@@ -0,0 +1,254 @@
+// vars.rs
+//
+// Srcmake - A templated source code generator written in Rust.
+// Copyright(C) 2024 Michael Furlong.
+//
+// This program is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3
+// of the License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.
+// If not, see <https://www.gnu.org/licenses/>.
+//
+use parsecfg::{Document, KeyValue};
+use std::fs;
+
+use crate::{box_error, name::is_valid_name, paths::get_extention, SMError, SMResult};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VarValue
+{
+	String(String),
+	Bool(bool),
+	Integer(i64),
+	Float(f64),
+	List(Vec<VarValue>),
+	Table(Vec<(String, VarValue)>),
+}
+impl VarValue
+{
+	// Lists are written one item per line, tables and nested lists have no text.
+	pub fn text(&self) -> Option<String>
+	{
+		match self
+		{
+			Self::String(s) => Some(s.clone()),
+			Self::Bool(b) => Some(b.to_string()),
+			Self::Integer(i) => Some(i.to_string()),
+			Self::Float(f) => Some(f.to_string()),
+			Self::List(l) => l
+				.iter()
+				.map(|v| match v
+				{
+					Self::List(_) | Self::Table(_) => None,
+					_ => v.text(),
+				})
+				.collect::<Option<Vec<String>>>()
+				.map(|l| l.join("\n")),
+			Self::Table(_) => None,
+		}
+	}
+}
+
+pub fn load_vars(path: &str) -> SMResult<Vec<(String, VarValue)>>
+{
+	let text = match fs::read_to_string(path)
+	{
+		Ok(t) => t,
+		Err(e) =>
+		{
+			return Err(Box::new(SMError::io(
+				"Failed reading variables file",
+				path,
+				e,
+			)))
+		}
+	};
+
+	let vars = match get_extention(path).to_lowercase().as_str()
+	{
+		"toml" => vars_from_toml(&text),
+		"json" => vars_from_json(&text),
+		"cfg" => vars_from_cfg(path, &text),
+		ext =>
+		{
+			return Err(box_error(&format!(
+				"Unsupported variables file extension '{ext}'; expected toml, json or cfg."
+			)))
+		}
+	};
+
+	match vars
+	{
+		Ok(v) => Ok(v),
+		Err(e) => Err(box_error(&format!(
+			"Failed loading variables from {path}: {e}"
+		))),
+	}
+}
+
+// Adds the variables, replacing any that already exist with the same key.
+pub fn merge_vars(vars: &mut Vec<(String, VarValue)>, other: Vec<(String, VarValue)>)
+{
+	for (key, value) in other
+	{
+		match vars.iter_mut().find(|(k, _)| *k == key)
+		{
+			Some(v) => v.1 = value,
+			None => vars.push((key, value)),
+		}
+	}
+}
+
+// Variables with text become macros of the same name in uppercase, with the keys of nested
+// tables joined by `_`, so `class.name` becomes `$CLASS_NAME$`.
+pub fn var_macros(vars: &[(String, VarValue)]) -> Vec<(String, String)>
+{
+	let mut macros: Vec<(String, String)> = Vec::new();
+
+	for (key, value) in vars
+	{
+		add_var_macros(&mut macros, &key.to_uppercase(), value);
+	}
+
+	macros
+}
+fn add_var_macros(macros: &mut Vec<(String, String)>, name: &str, value: &VarValue)
+{
+	match value
+	{
+		VarValue::Table(t) =>
+		{
+			for (key, v) in t
+			{
+				add_var_macros(macros, &format!("{name}_{}", key.to_uppercase()), v);
+			}
+		}
+		_ =>
+		{
+			if let Some(text) = value.text()
+			{
+				if is_valid_name(name)
+				{
+					macros.push((format!("${name}$"), text));
+				}
+			}
+		}
+	}
+}
+
+fn vars_from_toml(text: &str) -> SMResult<Vec<(String, VarValue)>>
+{
+	fn convert(value: toml::Value) -> VarValue
+	{
+		match value
+		{
+			toml::Value::String(s) => VarValue::String(s),
+			toml::Value::Integer(i) => VarValue::Integer(i),
+			toml::Value::Float(f) => VarValue::Float(f),
+			toml::Value::Boolean(b) => VarValue::Bool(b),
+			toml::Value::Datetime(d) => VarValue::String(d.to_string()),
+			toml::Value::Array(a) => VarValue::List(a.into_iter().map(convert).collect()),
+			toml::Value::Table(t) =>
+			{
+				VarValue::Table(t.into_iter().map(|(k, v)| (k, convert(v))).collect())
+			}
+		}
+	}
+
+	match text.parse::<toml::Table>()
+	{
+		Ok(t) => Ok(t.into_iter().map(|(k, v)| (k, convert(v))).collect()),
+		Err(e) => Err(box_error(&format!("{e}"))),
+	}
+}
+
+fn vars_from_json(text: &str) -> SMResult<Vec<(String, VarValue)>>
+{
+	fn convert(value: serde_json::Value) -> VarValue
+	{
+		match value
+		{
+			serde_json::Value::Null => VarValue::String(String::new()),
+			serde_json::Value::Bool(b) => VarValue::Bool(b),
+			serde_json::Value::Number(n) => match n.as_i64()
+			{
+				Some(i) => VarValue::Integer(i),
+				None => VarValue::Float(n.as_f64().unwrap_or_default()),
+			},
+			serde_json::Value::String(s) => VarValue::String(s),
+			serde_json::Value::Array(a) => VarValue::List(a.into_iter().map(convert).collect()),
+			serde_json::Value::Object(o) =>
+			{
+				VarValue::Table(o.into_iter().map(|(k, v)| (k, convert(v))).collect())
+			}
+		}
+	}
+
+	match serde_json::from_str::<serde_json::Value>(text)
+	{
+		Ok(serde_json::Value::Object(o)) =>
+		{
+			Ok(o.into_iter().map(|(k, v)| (k, convert(v))).collect())
+		}
+		Ok(_) => Err(box_error("The top level value must be an object.")),
+		Err(e) => Err(box_error(&format!("{e}"))),
+	}
+}
+
+fn vars_from_cfg(path: &str, text: &str) -> SMResult<Vec<(String, VarValue)>>
+{
+	let doc = Document::from_file(path)?;
+	let mut vars: Vec<(String, VarValue)> = Vec::new();
+	let mut section = None;
+	let mut in_section = false;
+
+	// Sections only group keys, so the keys of every section are read in order.
+	for line in text.lines().map(|l| l.trim())
+	{
+		if line.starts_with('[') && line.ends_with(']')
+		{
+			section = doc.get(line[1..line.len() - 1].trim());
+			in_section = true;
+			continue;
+		}
+
+		let key = match line.split_once('=')
+		{
+			Some((k, _)) if is_valid_name(k.trim()) => k.trim(),
+			_ => continue,
+		};
+
+		// Cfg documents only hold keys in sections, so these would otherwise be lost.
+		if !in_section
+		{
+			return Err(box_error(&format!(
+				"'{key}' is not in a section, cfg variables must follow a [section] header."
+			)));
+		}
+
+		let value = match section.and_then(|s| s.get(key)).map(|kv| &kv.value)
+		{
+			Some(KeyValue::String(s)) => VarValue::String(s.clone()),
+			Some(KeyValue::StringArray(a)) =>
+			{
+				VarValue::List(a.iter().map(|s| VarValue::String(s.clone())).collect())
+			}
+			Some(_) =>
+			{
+				return Err(box_error(&format!(
+					"'{key}' must be a string or an array of strings."
+				)))
+			}
+			None => continue,
+		};
+
+		merge_vars(&mut vars, vec![(String::from(key), value)]);
+	}
+
+	Ok(vars)
+}
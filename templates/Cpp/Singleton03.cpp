@@ -3,7 +3,7 @@
 // $AUTHOR$ $DATETIME$
 //
 
-#include "$NAME$.$HEADER_EXT$"
+#include "$SIBLING:hpp$"
 
 $NAMESPACE_BEGIN$
 
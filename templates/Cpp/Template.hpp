@@ -23,6 +23,6 @@ private:
 
 $NAMESPACE_END$
 
-#include "$NAME$.$INLINE_EXT$"
+#include "$SIBLING:inl$"
 
 #endif
@@ -1,4 +1,5 @@
 // $FILE_NAME$.$FILE_EXT$ //
+//! The $CRATE_NAME$ crate.
 
 $USES$
 
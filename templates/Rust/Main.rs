@@ -4,5 +4,5 @@ $USES$
 
 fn main()
 {
-	println!( "Hello World!" );
+	println!( "Hello from $PROJECT_NAME$!" );
 }